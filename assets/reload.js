@@ -0,0 +1,15 @@
+// Reloads the page when the blog is rebuilt by `milky serve`
+(() => {
+    let version;
+    setInterval(async () => {
+        try {
+            const res = await fetch("/.milky/version", { cache: "no-store" });
+            const current = await res.text();
+            if (version !== undefined && version !== current) {
+                location.reload();
+            }
+
+            version = current;
+        } catch { }
+    }, 500);
+})();
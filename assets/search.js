@@ -0,0 +1,34 @@
+// Filters the article list with the search index generated by milky
+(async () => {
+    const search = document.querySelector(".search");
+    const input = search?.querySelector("input");
+    if (!input) {
+        return;
+    }
+
+    let index;
+    try {
+        const res = await fetch(input.dataset.index);
+        index = await res.json();
+    } catch {
+        return;
+    }
+
+    const tokenize = (text) => text.toLowerCase().split(/[^\p{L}\p{N}]+/u).filter((w) => w);
+    const entries = new Map(index.map((entry) => [
+        entry.slug,
+        tokenize(entry.title).concat(entry.words.split(" ")),
+    ]));
+
+    const items = document.querySelectorAll(".list-item");
+    input.addEventListener("input", () => {
+        const query = tokenize(input.value);
+        for (const item of items) {
+            const words = entries.get(item.dataset.slug) ?? [];
+            const found = query.every((q) => words.some((w) => w.startsWith(q)));
+            item.hidden = !found;
+        }
+    });
+
+    search.hidden = false;
+})();
@@ -0,0 +1,132 @@
+use {
+    crate::{html::Rendered, log::debug},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::BTreeMap,
+        env, fs,
+        hash::{DefaultHasher, Hash, Hasher},
+        path::{Path, PathBuf},
+    },
+};
+
+/// Hashes any value to detect changes of build inputs.
+pub fn hash<H>(value: &H) -> u64
+where
+    H: Hash + ?Sized,
+{
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The build cache.
+///
+/// Stores hashes of inputs of every output file and rendered articles,
+/// so an unchanged output isn't generated again.
+/// Only entries used by the current build are saved back.
+pub struct Cache {
+    out: PathBuf,
+    prev: Data,
+    next: Data,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    fingerprint: String,
+    #[serde(default)]
+    outputs: BTreeMap<String, String>,
+    #[serde(default)]
+    rendered: BTreeMap<String, Source>,
+}
+
+/// The article rendering which is expensive to generate.
+#[derive(Serialize, Deserialize)]
+struct Source {
+    hash: String,
+    rendered: Rendered,
+}
+
+impl Cache {
+    const NAME: &str = ".milky-cache.toml";
+
+    pub fn read(out: &Path, force: bool) -> Self {
+        let fingerprint = fingerprint();
+        let prev = fs::read_to_string(out.join(Self::NAME))
+            .ok()
+            .and_then(|s| toml::from_str(&s).ok())
+            .filter(|prev: &Data| !force && prev.fingerprint == fingerprint)
+            .unwrap_or_else(|| {
+                debug!("the build cache is empty");
+                Data::default()
+            });
+
+        Self {
+            out: out.to_owned(),
+            prev,
+            next: Data {
+                fingerprint,
+                ..Data::default()
+            },
+        }
+    }
+
+    /// Checks whether the output file is up to date with its inputs hash.
+    pub fn fresh(&mut self, output: &Path, inputs: u64) -> bool {
+        let key = output.strip_prefix(&self.out).unwrap_or(output);
+        let key = key.to_string_lossy().into_owned();
+        let inputs = format!("{inputs:016x}");
+        let fresh = self.prev.outputs.get(&key) == Some(&inputs) && output.exists();
+
+        self.next.outputs.insert(key, inputs);
+        fresh
+    }
+
+    /// Checks whether the output file is produced by the current build.
+    pub fn produced(&self, output: &Path) -> bool {
+        let key = output.strip_prefix(&self.out).unwrap_or(output);
+        let key = key.to_string_lossy();
+        key == Self::NAME || self.next.outputs.contains_key(&*key)
+    }
+
+    /// Returns the cached rendered article if its inputs are unchanged.
+    pub fn rendered(&mut self, source: &Path, inputs: u64) -> Option<Rendered> {
+        let key = source.to_string_lossy().into_owned();
+        let hash = format!("{inputs:016x}");
+        let source = self.prev.rendered.remove(&key)?;
+        if source.hash != hash {
+            return None;
+        }
+
+        let rendered = source.rendered.clone();
+        self.next.rendered.insert(key, source);
+        Some(rendered)
+    }
+
+    pub fn render(&mut self, source: &Path, inputs: u64, rendered: Rendered) {
+        let key = source.to_string_lossy().into_owned();
+        let hash = format!("{inputs:016x}");
+        self.next.rendered.insert(key, Source { hash, rendered });
+    }
+
+    pub fn write(self) {
+        let Ok(cache) = toml::to_string(&self.next) else {
+            eprintln!("failed to serialize the build cache");
+            return;
+        };
+
+        let path = self.out.join(Self::NAME);
+        if fs::write(&path, cache).is_err() {
+            eprintln!("failed to write file {}", path.display());
+        }
+    }
+}
+
+/// Identifies the running binary, any change of it invalidates the cache.
+fn fingerprint() -> String {
+    let bin = env::current_exe().and_then(fs::read).unwrap_or_else(|_| {
+        debug!("failed to read the binary, use the version as a fingerprint");
+        env!("CARGO_PKG_VERSION").as_bytes().to_vec()
+    });
+
+    format!("{:016x}", hash(&bin))
+}
@@ -0,0 +1,206 @@
+use {
+    crate::{date::Date, log::Level},
+    std::{fmt, path::PathBuf},
+};
+
+pub const USAGE: &str = "\
+Usage: milky [build|serve|clean] [OPTIONS]
+
+Commands:
+    build             Generate the blog (default)
+    serve             Generate the blog, serve it on localhost and rebuild on changes
+    clean             Generate the blog and remove output files it didn't produce
+    help              Print this message
+
+Options:
+    --root DIR        Directory with the blog sources [default: .]
+    --out DIR         Output directory [default: ROOT/dist]
+    --config FILE     Configuration file [default: ROOT/Milky.toml]
+    --port PORT       Port of the local server [default: 8000]
+    --force           Regenerate all files ignoring the build cache
+    --drafts          Generate draft articles too
+    --prune           Remove output files the build didn't produce
+    --date DATE       The current date, articles dated after it are deferred
+                      [default: SOURCE_DATE_EPOCH or today]
+    --now DATE        Alias of --date
+    -q, --quiet       Print only warnings and errors
+    -v, --verbose     Print detailed progress
+    -h, --help        Print this message";
+
+pub struct Cli {
+    pub command: Command,
+    pub level: Level,
+}
+
+pub enum Command {
+    Build(Build),
+    Serve(Build, u16),
+    Help,
+}
+
+/// The build settings.
+pub struct Build {
+    pub root: PathBuf,
+    pub out: PathBuf,
+    pub config: PathBuf,
+    pub force: bool,
+    /// Generates draft articles.
+    pub drafts: bool,
+    /// Overrides the current date.
+    pub now: Option<Date>,
+    /// Removes stale output files.
+    pub prune: bool,
+}
+
+pub fn parse<I>(args: I) -> Result<Cli, Error>
+where
+    I: IntoIterator<Item = String>,
+{
+    enum Name {
+        Build,
+        Serve,
+        Clean,
+        Help,
+    }
+
+    let mut args = args.into_iter().peekable();
+    let mut name = match args.next_if(|arg| !arg.starts_with('-')).as_deref() {
+        Some("build") | None => Name::Build,
+        Some("serve") => Name::Serve,
+        Some("clean") => Name::Clean,
+        Some("help") => Name::Help,
+        Some(arg) => return Err(Error::UnknownCommand(arg.to_owned())),
+    };
+
+    let mut root = None;
+    let mut out = None;
+    let mut config = None;
+    let mut port = None;
+    let mut force = false;
+    let mut drafts = false;
+    let mut now = None;
+    let mut prune = false;
+    let mut level = Level::Normal;
+
+    while let Some(arg) = args.next() {
+        let mut value = |option| args.next().ok_or(Error::MissingValue(option));
+
+        match arg.as_str() {
+            "--root" => root = Some(PathBuf::from(value("--root")?)),
+            "--out" => out = Some(PathBuf::from(value("--out")?)),
+            "--config" => config = Some(PathBuf::from(value("--config")?)),
+            "--port" => {
+                let value = value("--port")?;
+                let parsed = value
+                    .parse()
+                    .map_err(|_| Error::InvalidValue("--port", value))?;
+                port = Some(parsed);
+            }
+            "--date" | "--now" => {
+                let value = value("--date")?;
+                let parsed = Date::parse(&value).ok_or(Error::InvalidValue("--date", value))?;
+                now = Some(parsed);
+            }
+            "--force" => force = true,
+            "--drafts" => drafts = true,
+            "--prune" => prune = true,
+            "-q" | "--quiet" => level = Level::Quiet,
+            "-v" | "--verbose" => level = Level::Verbose,
+            "-h" | "--help" => name = Name::Help,
+            _ => return Err(Error::UnknownOption(arg)),
+        }
+    }
+
+    let root = root.unwrap_or_default();
+    let build = Build {
+        out: out.unwrap_or_else(|| root.join("dist")),
+        config: config.unwrap_or_else(|| root.join("Milky.toml")),
+        root,
+        force,
+        drafts,
+        now,
+        prune: prune || matches!(name, Name::Clean),
+    };
+
+    let command = match name {
+        Name::Build | Name::Clean if port.is_some() => {
+            return Err(Error::UnknownOption(String::from("--port")));
+        }
+        Name::Build | Name::Clean => Command::Build(build),
+        Name::Serve => Command::Serve(build, port.unwrap_or(8000)),
+        Name::Help => Command::Help,
+    };
+
+    Ok(Cli { command, level })
+}
+
+pub enum Error {
+    UnknownCommand(String),
+    UnknownOption(String),
+    MissingValue(&'static str),
+    InvalidValue(&'static str, String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownCommand(command) => write!(f, "unknown command {command}"),
+            Self::UnknownOption(option) => write!(f, "unknown option {option}"),
+            Self::MissingValue(option) => write!(f, "option {option} requires a value"),
+            Self::InvalidValue(option, value) => {
+                write!(f, "invalid value {value} of option {option}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_build(args: &[&str]) -> Build {
+        let cli = parse(args.iter().map(|s| s.to_string())).ok();
+        let Some(Cli {
+            command: Command::Build(build),
+            ..
+        }) = cli
+        else {
+            panic!("expected the build command");
+        };
+
+        build
+    }
+
+    #[test]
+    fn defaults() {
+        let build = parse_build(&[]);
+        assert_eq!(build.root, PathBuf::new());
+        assert_eq!(build.out, PathBuf::from("dist"));
+        assert_eq!(build.config, PathBuf::from("Milky.toml"));
+        assert!(!build.drafts, "drafts are skipped by default");
+        assert!(!build.prune, "outputs are kept by default");
+        assert!(parse_build(&["clean"]).prune, "clean prunes outputs");
+    }
+
+    #[test]
+    fn paths() {
+        let build = parse_build(&["build", "--root", "blog", "--out", "public"]);
+        assert_eq!(build.root, PathBuf::from("blog"));
+        assert_eq!(build.out, PathBuf::from("public"));
+        assert_eq!(build.config, PathBuf::from("blog/Milky.toml"));
+    }
+
+    #[test]
+    fn errors() {
+        let parse = |args: &[&str]| parse(args.iter().map(|s| s.to_string())).err();
+        assert!(
+            matches!(parse(&["deploy"]), Some(Error::UnknownCommand(_))),
+            "unknown command",
+        );
+
+        assert!(
+            matches!(parse(&["--out"]), Some(Error::MissingValue("--out"))),
+            "missing value",
+        );
+    }
+}
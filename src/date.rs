@@ -1,67 +1,327 @@
 use {
-    crate::lang::Localizer,
+    crate::{lang::Localizer, log},
     serde::{Deserialize, Serialize},
-    std::fmt::Write,
-    time::{Month, OffsetDateTime},
+    std::{cmp::Ordering, env, fmt::Write},
+    time::{Month, OffsetDateTime, Time, UtcOffset},
+    toml::value::{self, Datetime, Offset},
 };
 
+/// Returns the current date and time.
+///
+/// The `SOURCE_DATE_EPOCH` variable takes precedence over the clock
+/// to make builds reproducible.
 pub fn now() -> Date {
-    let date = OffsetDateTime::now_local()
-        .inspect_err(|e| eprintln!("{e}"))
-        .unwrap_or(OffsetDateTime::UNIX_EPOCH)
-        .date();
-
-    Date {
-        day: date.day(),
-        month: date.month(),
-        year: date.year(),
+    if let Ok(epoch) = env::var("SOURCE_DATE_EPOCH") {
+        match from_epoch(&epoch) {
+            Some(date) => return date,
+            None => log::warning!("invalid SOURCE_DATE_EPOCH value {epoch:?}, use the clock"),
+        }
     }
+
+    let now = OffsetDateTime::now_local().unwrap_or_else(|e| {
+        log::warning!("{e}, use the UTC date");
+        OffsetDateTime::now_utc()
+    });
+
+    Date::from(now)
 }
 
-#[derive(Clone, Copy, Serialize, Deserialize)]
+/// Converts unix seconds to the UTC date and time.
+fn from_epoch(epoch: &str) -> Option<Date> {
+    let secs = epoch.trim().parse().ok()?;
+    let datetime = OffsetDateTime::from_unix_timestamp(secs).ok()?;
+    Some(Date::from(datetime))
+}
+
+/// The calendar date with an optional time of day.
+///
+/// Serialized as a TOML date like `2025-01-31`
+/// or an offset date time like `2025-01-31T10:00:00+03:00`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Date {
-    pub day: u8,
-    #[serde(with = "conv")]
-    pub month: Month,
-    pub year: i32,
+    date: time::Date,
+    /// The time of day with its UTC offset.
+    time: Option<(Time, UtcOffset)>,
 }
 
 impl Date {
+    /// Parses the date from a string like `2025-01-31`
+    /// or `2025-01-31T10:00:00+03:00`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let datetime = s.parse().ok()?;
+        from_datetime(datetime).ok()
+    }
+
+    /// Checks whether the date is after the current one.
+    ///
+    /// A date without time is compared by days,
+    /// so it's not after the current date on its own day.
+    pub fn after(self, now: Self) -> bool {
+        match self.time {
+            Some(_) => self > now,
+            None => self.date > now.date,
+        }
+    }
+
+    /// Checks whether the date is before the other one.
+    ///
+    /// Dates are compared by days if either has no time.
+    pub fn before(self, other: Self) -> bool {
+        match (self.time, other.time) {
+            (Some(_), Some(_)) => self < other,
+            _ => self.date < other.date,
+        }
+    }
+
+    /// The unix timestamp used to order dates,
+    /// a date without time is midnight UTC.
+    fn key(self) -> (i64, Option<i32>) {
+        match self.time {
+            Some((time, offset)) => (
+                self.date
+                    .with_time(time)
+                    .assume_offset(offset)
+                    .unix_timestamp(),
+                Some(offset.whole_seconds()),
+            ),
+            None => (self.date.midnight().assume_utc().unix_timestamp(), None),
+        }
+    }
+
+    /// Renders the calendar date only.
     pub fn render(self, l: Localizer<'_>) -> impl maud::Render {
-        struct Render<'loc>(Date, Localizer<'loc>);
+        struct Render<'loc>(time::Date, Localizer<'loc>);
 
         impl maud::Render for Render<'_> {
             fn render_to(&self, buffer: &mut String) {
-                let Self(Date { day, month, year }, l) = self;
-                let month_name = l.month(*month);
-                _ = write!(buffer, "{day} {month_name} {year}");
+                let &Self(date, l) = self;
+                let month_name = l.month(date.month());
+                _ = write!(buffer, "{} {month_name} {}", date.day(), date.year());
             }
         }
 
-        Render(self, l)
+        Render(self.date, l)
+    }
+
+    /// Formats the date as an ISO 8601 calendar date.
+    pub fn iso(self) -> String {
+        let date = self.date;
+        let month = u8::from(date.month());
+        format!("{:04}-{month:02}-{:02}", date.year(), date.day())
+    }
+
+    /// Formats the date for machine-readable output like `<time datetime>`.
+    ///
+    /// It's the calendar date or the RFC 3339 timestamp if the time is known.
+    pub fn datetime(self) -> String {
+        match self.time {
+            Some(_) => self.rfc3339(),
+            None => self.iso(),
+        }
+    }
+
+    /// Formats the date as an RFC 3339 timestamp, midnight UTC if the time is unknown.
+    pub fn rfc3339(self) -> String {
+        let (time, offset) = self.time.unwrap_or((Time::MIDNIGHT, UtcOffset::UTC));
+        let (hour, minute, second) = time.as_hms();
+        let mut s = format!("{}T{hour:02}:{minute:02}:{second:02}", self.iso());
+        if offset.is_utc() {
+            s.push('Z');
+        } else {
+            let (h, m, _) = offset.as_hms();
+            let sign = if offset.is_negative() { '-' } else { '+' };
+            _ = write!(s, "{sign}{:02}:{:02}", h.abs(), m.abs());
+        }
+
+        s
+    }
+
+    /// Formats the date as an RFC 2822 timestamp, midnight UTC if the time is unknown.
+    pub fn rfc2822(self) -> String {
+        let date = self.date;
+        let (time, offset) = self.time.unwrap_or((Time::MIDNIGHT, UtcOffset::UTC));
+        let (hour, minute, second) = time.as_hms();
+        let (h, m, _) = offset.as_hms();
+        let sign = if offset.is_negative() { '-' } else { '+' };
+        format!(
+            "{:.3}, {:02} {:.3} {:04} {hour:02}:{minute:02}:{second:02} {sign}{:02}{:02}",
+            date.weekday(),
+            date.day(),
+            date.month(),
+            date.year(),
+            h.abs(),
+            m.abs(),
+        )
     }
 }
 
-mod conv {
-    use {
-        super::*,
-        serde::{Deserializer, Serializer, de::Error},
-    };
+impl From<OffsetDateTime> for Date {
+    fn from(datetime: OffsetDateTime) -> Self {
+        let time = datetime
+            .time()
+            .replace_nanosecond(0)
+            .unwrap_or(Time::MIDNIGHT);
+
+        Self {
+            date: datetime.date(),
+            time: Some((time, datetime.offset())),
+        }
+    }
+}
+
+impl PartialOrd for Date {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-    #[allow(clippy::trivially_copy_pass_by_ref, reason = "serde derive API")]
-    pub fn serialize<S>(&month: &Month, serializer: S) -> Result<S::Ok, S::Error>
+impl Ord for Date {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        S: Serializer,
+        S: serde::Serializer,
     {
-        serializer.serialize_u8(u8::from(month))
+        use serde::ser::Error;
+
+        let date = self.date;
+        let year = u16::try_from(date.year()).map_err(|_| S::Error::custom("invalid year"))?;
+        let mut datetime = Datetime {
+            date: Some(value::Date {
+                year,
+                month: u8::from(date.month()),
+                day: date.day(),
+            }),
+            time: None,
+            offset: None,
+        };
+
+        if let Some((time, offset)) = self.time {
+            let (hour, minute, second) = time.as_hms();
+            datetime.time = Some(value::Time {
+                hour,
+                minute,
+                second,
+                nanosecond: 0,
+            });
+
+            datetime.offset = Some(if offset.is_utc() {
+                Offset::Z
+            } else {
+                let minutes = offset.whole_minutes();
+                Offset::Custom { minutes }
+            });
+        }
+
+        datetime.serialize(serializer)
     }
+}
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<Month, D::Error>
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
-        D: Deserializer<'de>,
+        D: serde::Deserializer<'de>,
     {
-        let u = u8::deserialize(deserializer)?;
-        Month::try_from(u)
-            .map_err(|e| D::Error::custom(format!("failed to deserialize month: {e}")))
+        use serde::de::Error;
+
+        let datetime = Datetime::deserialize(deserializer)?;
+        from_datetime(datetime).map_err(D::Error::custom)
+    }
+}
+
+fn from_datetime(datetime: Datetime) -> Result<Date, String> {
+    let Some(date) = datetime.date else {
+        return Err(String::from("expected a date"));
+    };
+
+    let month = Month::try_from(date.month).map_err(|e| format!("invalid month: {e}"))?;
+    let date = time::Date::from_calendar_date(i32::from(date.year), month, date.day)
+        .map_err(|e| format!("invalid date: {e}"))?;
+
+    let time = match (datetime.time, datetime.offset) {
+        (None, _) => None,
+        (Some(_), None) => return Err(String::from("expected a time with an UTC offset")),
+        (Some(time), Some(offset)) => {
+            let time = Time::from_hms(time.hour, time.minute, time.second)
+                .map_err(|e| format!("invalid time: {e}"))?;
+
+            let minutes = match offset {
+                Offset::Z => 0,
+                Offset::Custom { minutes } => minutes,
+            };
+
+            let offset = UtcOffset::from_whole_seconds(i32::from(minutes) * 60)
+                .map_err(|e| format!("invalid offset: {e}"))?;
+
+            Some((time, offset))
+        }
+    };
+
+    Ok(Date { date, time })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps() {
+        let date = Date::parse("2024-02-03").expect("valid date");
+        assert_eq!(date.datetime(), "2024-02-03");
+        assert_eq!(date.rfc3339(), "2024-02-03T00:00:00Z");
+        assert_eq!(date.rfc2822(), "Sat, 03 Feb 2024 00:00:00 +0000");
+
+        let date = Date::parse("2024-02-03T10:05:00-03:30").expect("valid date");
+        assert_eq!(date.datetime(), "2024-02-03T10:05:00-03:30");
+        assert_eq!(date.rfc3339(), "2024-02-03T10:05:00-03:30");
+        assert_eq!(date.rfc2822(), "Sat, 03 Feb 2024 10:05:00 -0330");
+    }
+
+    #[test]
+    fn parse() {
+        let date = Date::parse("2024-02-03").expect("valid date");
+        assert_eq!(date.iso(), "2024-02-03");
+        assert!(Date::parse("2024-02-30").is_none(), "invalid day");
+        assert!(Date::parse("today").is_none(), "not a date");
+        assert!(Date::parse("2024-02-03T10:00:00").is_none(), "no offset");
+        assert!(
+            Date::parse("2024-02-03") < Date::parse("2024-10-01"),
+            "dates are ordered",
+        );
+
+        assert!(
+            Date::parse("2024-02-03T10:00:00+03:00") < Date::parse("2024-02-03T08:00:00Z"),
+            "times are ordered by the instant",
+        );
+    }
+
+    #[test]
+    fn after() {
+        let now = Date::parse("2024-02-03T10:00:00+03:00").expect("valid date");
+        let after = |s| Date::parse(s).expect("valid date").after(now);
+        assert!(!after("2024-02-03"), "the same day");
+        assert!(after("2024-02-04"), "the next day");
+        assert!(after("2024-02-03T11:00:00+03:00"), "the next hour");
+    }
+
+    #[test]
+    fn before() {
+        let date = Date::parse("2024-02-03T10:00:00+03:00").expect("valid date");
+        let before = |s| Date::parse(s).expect("valid date").before(date);
+        assert!(!before("2024-02-03"), "the same day");
+        assert!(before("2024-02-02"), "the previous day");
+        assert!(before("2024-02-03T09:00:00+03:00"), "the previous hour");
+        assert!(!before("2024-02-03T10:00:00+03:00"), "the same time");
+    }
+
+    #[test]
+    fn epoch() {
+        let date = from_epoch("1706918400").expect("valid epoch");
+        assert_eq!(date.rfc3339(), "2024-02-03T00:00:00Z");
+        assert!(from_epoch("now").is_none(), "not a number");
     }
 }
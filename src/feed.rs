@@ -0,0 +1,114 @@
+use {
+    crate::{
+        date::Date,
+        html::{self, Post},
+        lang::Lang,
+    },
+    std::fmt::Write,
+};
+
+pub struct Feed<'art> {
+    pub base_url: &'art str,
+    pub blog: &'art str,
+    pub lang: Lang,
+    pub posts: &'art [Post<'art>],
+}
+
+impl Feed<'_> {
+    fn page_url(&self, name: &str) -> String {
+        format!("{}/{}/{name}.html", self.base_url, self.lang)
+    }
+
+    fn list_url(&self) -> String {
+        format!("{}/{}.html", self.base_url, self.lang)
+    }
+}
+
+pub fn atom(feed: &Feed<'_>) -> String {
+    let mut xml = String::new();
+    let list_url = feed.list_url();
+    let updated = feed
+        .posts
+        .iter()
+        .map(Post::modified)
+        .max()
+        .map(Date::rfc3339)
+        .unwrap_or_default();
+
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>");
+    _ = write!(
+        xml,
+        "<feed xmlns=\"http://www.w3.org/2005/Atom\" xml:lang=\"{}\">",
+        feed.lang,
+    );
+
+    tag(&mut xml, "title", feed.blog);
+    tag(&mut xml, "id", &list_url);
+    _ = write!(xml, "<link href=\"{list_url}\"/>");
+    _ = write!(
+        xml,
+        "<link rel=\"self\" href=\"{}/{}.atom\"/>",
+        feed.base_url, feed.lang,
+    );
+
+    tag(&mut xml, "updated", &updated);
+    xml.push_str("<author>");
+    tag(&mut xml, "name", feed.blog);
+    xml.push_str("</author>");
+
+    for post in feed.posts {
+        let url = feed.page_url(post.name);
+
+        xml.push_str("<entry>");
+        tag(&mut xml, "title", post.title);
+        tag(&mut xml, "id", &url);
+        _ = write!(xml, "<link href=\"{url}\"/>");
+        tag(&mut xml, "published", &post.date.rfc3339());
+        tag(&mut xml, "updated", &post.modified().rfc3339());
+        if let Some(description) = &post.description {
+            tag(&mut xml, "summary", description);
+        }
+
+        // relative links of the content are resolved against the page url
+        _ = write!(xml, "<content type=\"html\" xml:base=\"{url}\">");
+        html::escape(&post.content, &mut xml);
+        xml.push_str("</content>");
+        xml.push_str("</entry>");
+    }
+
+    xml.push_str("</feed>");
+    xml
+}
+
+pub fn rss(feed: &Feed<'_>) -> String {
+    let mut xml = String::new();
+    let list_url = feed.list_url();
+
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>");
+    xml.push_str("<rss version=\"2.0\"><channel>");
+    tag(&mut xml, "title", feed.blog);
+    tag(&mut xml, "link", &list_url);
+    tag(&mut xml, "description", feed.blog);
+    tag(&mut xml, "language", &feed.lang.to_string());
+
+    for post in feed.posts {
+        let url = feed.page_url(post.name);
+
+        xml.push_str("<item>");
+        tag(&mut xml, "title", post.title);
+        tag(&mut xml, "link", &url);
+        tag(&mut xml, "guid", &url);
+        tag(&mut xml, "pubDate", &post.date.rfc2822());
+        tag(&mut xml, "description", &post.content);
+        xml.push_str("</item>");
+    }
+
+    xml.push_str("</channel></rss>");
+    xml
+}
+
+fn tag(xml: &mut String, name: &str, text: &str) {
+    _ = write!(xml, "<{name}>");
+    html::escape(text, xml);
+    _ = write!(xml, "</{name}>");
+}
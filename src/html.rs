@@ -4,16 +4,20 @@ use {
         date::Date,
         icon::Icon,
         lang::{Lang, Localizer},
-        rust,
+        rust, search,
     },
-    pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd},
+    pulldown_cmark::{
+        Alignment, BlockQuoteKind, CodeBlockKind, CowStr, Event, HeadingLevel, Options, Parser,
+        Tag, TagEnd, TextMergeWithOffset,
+    },
+    serde::{Deserialize, Serialize},
     std::{
         borrow::Cow,
         cell::RefCell,
-        cmp,
-        collections::{HashMap, HashSet},
-        fmt::Write,
-        iter,
+        collections::{BTreeSet, HashMap},
+        fmt::{self, Write},
+        iter, mem,
+        path::Path,
     },
 };
 
@@ -26,18 +30,48 @@ pub struct Make<'art> {
     pub l: Localizer<'art>,
     pub blog: &'art str,
     pub title: &'art str,
+    /// The page path relative to the output directory.
+    pub href: String,
+    /// The absolute url of the blog used for canonical links.
+    pub base_url: Option<&'art str>,
     pub translations: &'art mut dyn Iterator<Item = Translation>,
     pub social: &'art [Social],
+    pub feeds: Feeds,
+    /// Shows descriptions of posts in lists.
+    pub excerpts: bool,
+    /// Injects the live reload script used by the local server.
+    pub reload: bool,
     pub target: Target<'art>,
 }
 
+/// The feeds available for the page language.
+#[derive(Clone, Copy, Default)]
+pub struct Feeds {
+    pub atom: bool,
+    pub rss: bool,
+}
+
 pub enum Target<'art> {
     List(&'art [Post<'art>]),
+    Tag(&'art TagPosts<'art>),
+    Tags(&'art [TagPosts<'art>]),
     Article {
-        md: &'art str,
+        content: &'art str,
+        description: Option<&'art str>,
+        tags: &'art [Box<str>],
+        cover: Option<&'art str>,
+        /// The table of contents shown at the top.
+        toc: Option<&'art str>,
+        /// The first image of the article.
+        image: Option<&'art str>,
         date: Date,
-        index_href: String,
-        deps: &'art mut HashSet<Box<str>>,
+        updated: Option<Date>,
+        /// The reading time in minutes.
+        minutes: u32,
+        /// The list page of the language, if it has listed articles.
+        index_href: Option<String>,
+        /// The article is listed, so its tags have pages.
+        listed: bool,
     },
 }
 
@@ -46,80 +80,286 @@ pub fn make(make: Make<'_>) -> maud::Markup {
         l,
         blog,
         title,
+        href,
+        base_url,
         translations,
         social,
+        feeds,
+        excerpts,
+        reload,
         target,
     } = make;
 
-    let translations_into_buttons = translations.map(Button::from_translation);
+    let translations: Vec<_> = translations.collect();
+    let translations_into_buttons = translations.iter().map(Button::from_translation);
+    let placeholder = maud::html! { div {} };
+    let lang = l.lang();
+    let alternates = Alternates {
+        lang,
+        href: &href,
+        translations: &translations,
+        base_url,
+    };
 
     match target {
         Target::List(posts) => {
-            let placeholder = maud::html! { div {} };
             let subtitle = subtitle(placeholder, translations_into_buttons, 0);
             let header = header(blog, title, subtitle);
-            page(blog, header, list(posts, l), social, 0)
+            let head = Head {
+                title: blog,
+                description: None,
+                keywords: &[],
+                l,
+                alternates,
+                blog,
+                published: None,
+                feeds,
+                reload,
+            };
+
+            let content = maud::html! {
+                // the search box is shown by the script once the index is loaded
+                .content .search hidden {
+                    input type="search"
+                        placeholder=(l.search())
+                        data-index=(format!("{lang}.search.json"));
+                }
+                (list(posts, l, 0, excerpts))
+                script src="search.js" defer {}
+            };
+
+            page(head, header, content, social, 0)
+        }
+        Target::Tag(TagPosts { tag, posts }) => {
+            let label = l.tag(tag);
+            let buttons = [
+                Button::articles(format!("{lang}.html"), l),
+                Button::tags(format!("{lang}/tags/index.html"), l),
+            ];
+
+            let buttons = buttons.into_iter().chain(translations_into_buttons);
+            let subtitle = subtitle(placeholder, buttons, 2);
+            let header = header(blog, label, subtitle);
+            let head = Head {
+                title: label,
+                description: None,
+                keywords: &[],
+                l,
+                alternates,
+                blog,
+                published: None,
+                feeds,
+                reload,
+            };
+
+            let list = list(posts.iter().copied(), l, 2, excerpts);
+            page(head, header, list, social, 2)
+        }
+        Target::Tags(tags) => {
+            let buttons = iter::once(Button::articles(format!("{lang}.html"), l))
+                .chain(translations_into_buttons);
+
+            let subtitle = subtitle(placeholder, buttons, 2);
+            let header = header(blog, l.tags(), subtitle);
+            let head = Head {
+                title: l.tags(),
+                description: None,
+                keywords: &[],
+                l,
+                alternates,
+                blog,
+                published: None,
+                feeds,
+                reload,
+            };
+
+            page(head, header, tag_list(tags, l), social, 2)
         }
         Target::Article {
-            md,
+            content,
+            description,
+            tags,
+            cover,
+            toc,
+            image,
             date,
+            updated,
+            minutes,
             index_href,
-            deps,
+            listed,
         } => {
-            let buttons =
-                iter::once(Button::articles(index_href, l)).chain(translations_into_buttons);
+            let buttons = index_href
+                .map(|href| Button::articles(href, l))
+                .into_iter()
+                .chain(translations_into_buttons);
 
-            let html = md_to_html(md, deps);
-            let date = date_block(date, l);
-            let subtitle = subtitle(date, buttons, 1);
+            let info = info(date, updated, minutes, tags, listed, l, 1);
+            let subtitle = subtitle(info, buttons, 1);
             let header = header(blog, title, subtitle);
-            page(title, header, article(&html), social, 1)
+            let head = Head {
+                title,
+                description,
+                keywords: tags,
+                l,
+                alternates,
+                blog,
+                published: Some(Published {
+                    date,
+                    updated,
+                    image: cover.or(image),
+                }),
+                feeds,
+                reload,
+            };
+
+            page(head, header, article(cover, toc, content), social, 1)
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Hash)]
 pub struct Post<'art> {
     pub name: &'art str,
     pub title: &'art str,
     pub date: Date,
+    pub updated: Option<Date>,
+    /// The reading time in minutes.
+    pub minutes: u32,
+    pub tags: &'art [Box<str>],
+    /// The description or the excerpt of the article.
+    pub description: Option<String>,
+    pub content: String,
+    /// The plain text used for search.
+    pub text: String,
 }
 
 impl Post<'_> {
-    pub fn by_date(&self) -> impl cmp::Ord + use<> {
-        let &Self {
-            date: Date { day, month, year },
-            ..
-        } = self;
+    /// Returns the date of the last change.
+    pub fn modified(&self) -> Date {
+        self.updated.unwrap_or(self.date)
+    }
+}
+
+/// Posts with the same tag.
+#[derive(Hash)]
+pub struct TagPosts<'art> {
+    pub tag: &'art str,
+    pub posts: Vec<&'art Post<'art>>,
+}
+
+/// Returns the path of the tag page relative to the output root.
+pub fn tag_href(lang: Lang, tag: &str) -> String {
+    format!("{lang}/tags/{}.html", slug(tag))
+}
 
-        (year, month as u8, day)
+/// Makes a url friendly identifier from the text.
+///
+/// Keeps unicode letters and digits in lowercase,
+/// joins the rest words with dashes.
+pub fn slug(s: &str) -> String {
+    let mut slug = String::new();
+    for word in s.split(|c: char| !c.is_alphanumeric()) {
+        if word.is_empty() {
+            continue;
+        }
+
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+
+        slug.extend(word.chars().flat_map(char::to_lowercase));
     }
+
+    slug
 }
 
-fn list(posts: &[Post<'_>], l: Localizer<'_>) -> maud::Markup {
+fn list<'art, P>(posts: P, l: Localizer<'_>, level: u8, excerpts: bool) -> maud::Markup
+where
+    P: IntoIterator<Item = &'art Post<'art>>,
+{
     let href = |name| format!("{}/{name}.html", l.lang());
 
     maud::html! {
         ul .content {
-            @for Post { name, title, date } in posts {
+            @for Post { name, title, date, updated, minutes, tags, description, .. } in posts {
+                li .list-item data-slug=(name) {
+                    a href=(relative_path(&href(name), level)) { (title) }
+                    (info(*date, *updated, *minutes, tags, true, l, level))
+                    @if let Some(description) = description.as_deref().filter(|_| excerpts) {
+                        p .excerpt { (description) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn tag_list(tags: &[TagPosts<'_>], l: Localizer<'_>) -> maud::Markup {
+    maud::html! {
+        ul .content {
+            @for TagPosts { tag, posts } in tags {
                 li .list-item {
-                    a href=(href(name)) { (title) }
-                    (date_block(*date, l))
+                    a href=(relative_path(&tag_href(l.lang(), tag), 2)) { (l.tag(tag)) }
+                    .date { (posts.len()) (Icon::Bookshelf) }
                 }
             }
         }
     }
 }
 
-fn article(article: &str) -> maud::Markup {
+fn article(cover: Option<&str>, toc: Option<&str>, article: &str) -> maud::Markup {
     maud::html! {
-        article .content { (maud::PreEscaped(article)) }
+        article .content {
+            @if let Some(cover) = cover {
+                img .cover src=(relative_path(cover, 1));
+            }
+            @if let Some(toc) = toc {
+                (maud::PreEscaped(toc))
+            }
+            (maud::PreEscaped(article))
+        }
     }
 }
 
-fn date_block(date: Date, l: Localizer<'_>) -> maud::Markup {
+/// Renders the date block with the reading time and tag chips.
+///
+/// Tags are plain labels if they have no pages.
+fn info(
+    date: Date,
+    updated: Option<Date>,
+    minutes: u32,
+    tags: &[Box<str>],
+    linked: bool,
+    l: Localizer<'_>,
+    level: u8,
+) -> maud::Markup {
+    let reading = l.reading(minutes);
+
     maud::html! {
-        .date { (date.render(l)) (Icon::Date) }
+        .info {
+            .date {
+                time datetime=(date.datetime()) { (date.render(l)) }
+                (Icon::Date)
+            }
+            @if let Some(updated) = updated.filter(|&updated| updated != date) {
+                .date {
+                    (l.updated()) " "
+                    time datetime=(updated.datetime()) { (updated.render(l)) }
+                }
+            }
+            @if !reading.is_empty() {
+                .date { (reading) (Icon::Clock) }
+            }
+            @for tag in tags {
+                @if linked {
+                    a .tag href=(relative_path(&tag_href(l.lang(), tag), level)) {
+                        (Icon::Tag) (l.tag(tag))
+                    }
+                } @else {
+                    span .tag { (Icon::Tag) (l.tag(tag)) }
+                }
+            }
+        }
     }
 }
 
@@ -145,11 +385,11 @@ struct Button<'art> {
 }
 
 impl<'art> Button<'art> {
-    fn from_translation(Translation { lang, href }: Translation) -> Self {
+    fn from_translation(Translation { lang, href }: &Translation) -> Self {
         Self {
             icon: Icon::Earth,
             label: Cow::Owned(lang.to_string()),
-            href,
+            href: href.clone(),
         }
     }
 
@@ -160,6 +400,14 @@ impl<'art> Button<'art> {
             href,
         }
     }
+
+    fn tags(href: String, l: Localizer<'art>) -> Self {
+        Self {
+            icon: Icon::Tag,
+            label: Cow::Borrowed(l.tags()),
+            href,
+        }
+    }
 }
 
 fn subtitle<'art, D, B>(date: D, buttons: B, level: u8) -> maud::Markup
@@ -179,30 +427,176 @@ where
     }
 }
 
-fn page<H, C>(title: &str, header: H, content: C, social: &[Social], level: u8) -> maud::Markup
+struct Head<'art> {
+    title: &'art str,
+    description: Option<&'art str>,
+    keywords: &'art [Box<str>],
+    l: Localizer<'art>,
+    alternates: Alternates<'art>,
+    blog: &'art str,
+    /// The article info shared in link previews.
+    published: Option<Published<'art>>,
+    feeds: Feeds,
+    reload: bool,
+}
+
+#[derive(Clone, Copy)]
+struct Published<'art> {
+    date: Date,
+    updated: Option<Date>,
+    /// The cover or the first image of the article.
+    image: Option<&'art str>,
+}
+
+/// The page with its translations.
+#[derive(Clone, Copy)]
+struct Alternates<'art> {
+    lang: Lang,
+    href: &'art str,
+    translations: &'art [Translation],
+    base_url: Option<&'art str>,
+}
+
+impl<'art> Alternates<'art> {
+    /// Returns hrefs of the page in all languages sorted by language.
+    fn hrefs(self) -> Vec<(Lang, &'art str)> {
+        let mut hrefs: Vec<_> = iter::once((self.lang, self.href))
+            .chain(self.translations.iter().map(|t| (t.lang, &*t.href)))
+            .collect();
+
+        hrefs.sort_unstable();
+        hrefs
+    }
+}
+
+fn page<H, C>(head: Head<'_>, header: H, content: C, social: &[Social], level: u8) -> maud::Markup
 where
     H: maud::Render,
     C: maud::Render,
 {
+    // link previews require absolute urls
+    let base_url = head.alternates.base_url;
+    let url = base_url.map(|base_url| page_url(head.alternates.href, level, Some(base_url)));
+    let image = head
+        .published
+        .and_then(|published| published.image)
+        .zip(base_url)
+        .map(|(image, base_url)| page_url(image, level, Some(base_url)));
+
+    let posting = head
+        .published
+        .map(|published| posting(&head, published, url.as_deref(), image.as_deref()));
+
+    let Head {
+        title,
+        description,
+        keywords,
+        l,
+        alternates,
+        blog,
+        published,
+        feeds,
+        reload,
+    } = head;
+
+    let lang = l.lang();
+
+    let hrefs = alternates.hrefs();
+
     maud::html! {
         (maud::DOCTYPE)
-        head {
-            meta charset="utf-8";
-            meta name="viewport" content="width=device-width, initial-scale=1.0";
-            meta name="theme-color" content="#282726";
-            link rel="icon" href=(relative_path("favicon.svg", level));
-            link rel="stylesheet" href=(relative_path("style.css", level));
-            title { (title) }
-        }
-        body {
-            style { (inline_css(level)) }
-            (header)
-            (content)
-            footer {
-                .socials {
-                    @for s in social {
-                        a .icon href=(s.href) aria-label=(s.icon.label()) target="_blank" {
-                            (s.icon)
+        html lang=(lang) {
+            head {
+                meta charset="utf-8";
+                meta name="viewport" content="width=device-width, initial-scale=1.0";
+                meta name="theme-color" content="#282726";
+                @if let Some(description) = description {
+                    meta name="description" content=(description);
+                }
+                @if !keywords.is_empty() {
+                    meta name="keywords" content=(keywords.join(", "));
+                }
+                link rel="icon" href=(relative_path("favicon.svg", level));
+                link rel="stylesheet" href=(relative_path("style.css", level));
+                @if base_url.is_some() {
+                    link rel="canonical" href=(page_url(alternates.href, level, base_url));
+                }
+                @if hrefs.len() > 1 {
+                    @for &(lang, href) in &hrefs {
+                        link rel="alternate" hreflang=(lang)
+                            href=(page_url(href, level, base_url));
+                    }
+                    // the first language is the default one
+                    @if let Some(&(_, href)) = hrefs.first() {
+                        link rel="alternate" hreflang="x-default"
+                            href=(page_url(href, level, base_url));
+                    }
+                }
+                meta property="og:site_name" content=(blog);
+                meta property="og:title" content=(title);
+                @if let Some(description) = description {
+                    meta property="og:description" content=(description);
+                }
+                meta property="og:type"
+                    content=(if published.is_some() { "article" } else { "website" });
+                @if let Some(url) = &url {
+                    meta property="og:url" content=(url);
+                }
+                @if let Some(locale) = l.locale() {
+                    meta property="og:locale" content=(locale);
+                }
+                @for &(other, _) in &hrefs {
+                    @if let Some(locale) = l.bind(other).locale().filter(|_| other != lang) {
+                        meta property="og:locale:alternate" content=(locale);
+                    }
+                }
+                @if let Some(image) = &image {
+                    meta property="og:image" content=(image);
+                }
+                @if let Some(Published { date, updated, .. }) = published {
+                    meta property="article:published_time" content=(date.rfc3339());
+                    @if let Some(updated) = updated {
+                        meta property="article:modified_time" content=(updated.rfc3339());
+                    }
+                    @for tag in keywords {
+                        meta property="article:tag" content=(tag);
+                    }
+                }
+                meta name="twitter:card"
+                    content=(if image.is_some() { "summary_large_image" } else { "summary" });
+                meta name="twitter:title" content=(title);
+                @if let Some(description) = description {
+                    meta name="twitter:description" content=(description);
+                }
+                @if let Some(image) = &image {
+                    meta name="twitter:image" content=(image);
+                }
+                @if let Some(posting) = posting {
+                    script type="application/ld+json" { (maud::PreEscaped(posting)) }
+                }
+                @if feeds.atom {
+                    link rel="alternate" type="application/atom+xml"
+                        href=(relative_path(&format!("{lang}.atom"), level));
+                }
+                @if feeds.rss {
+                    link rel="alternate" type="application/rss+xml"
+                        href=(relative_path(&format!("{lang}.rss"), level));
+                }
+                title { (title) }
+                @if reload {
+                    script { (maud::PreEscaped(include_str!("../assets/reload.js"))) }
+                }
+            }
+            body {
+                style { (inline_css(level)) }
+                (header)
+                (content)
+                footer {
+                    .socials {
+                        @for s in social {
+                            a .icon href=(s.href) aria-label=(s.icon.label()) target="_blank" {
+                                (s.icon)
+                            }
                         }
                     }
                 }
@@ -211,6 +605,67 @@ where
     }
 }
 
+/// Makes the `BlogPosting` structured data of the article.
+fn posting(
+    head: &Head<'_>,
+    published: Published<'_>,
+    url: Option<&str>,
+    image: Option<&str>,
+) -> String {
+    let mut json = String::from("{\"@context\":\"https://schema.org\",\"@type\":\"BlogPosting\"");
+    let mut field = |name, value: &str| {
+        _ = write!(json, ",\"{name}\":");
+        search::string(value, &mut json);
+    };
+
+    field("headline", head.title);
+    if let Some(description) = head.description {
+        field("description", description);
+    }
+
+    field("datePublished", &published.date.rfc3339());
+    let modified = published.updated.unwrap_or(published.date);
+    field("dateModified", &modified.rfc3339());
+    field("inLanguage", &head.l.lang().to_string());
+    if !head.keywords.is_empty() {
+        field("keywords", &head.keywords.join(", "));
+    }
+
+    if let Some(url) = url {
+        field("url", url);
+    }
+
+    if let Some(image) = image {
+        field("image", image);
+    }
+
+    json.push_str(",\"publisher\":{\"@type\":\"Organization\",\"name\":");
+    search::string(head.blog, &mut json);
+    json.push_str("}}");
+    json
+}
+
+/// Makes a page redirecting to the target.
+///
+/// The canonical link is absolute if the base url is known.
+pub fn redirect(href: &str, level: u8, base_url: Option<&str>) -> maud::Markup {
+    let canonical = page_url(href, level, base_url);
+    let href = relative_path(href, level);
+
+    maud::html! {
+        (maud::DOCTYPE)
+        head {
+            meta charset="utf-8";
+            title { "Redirect" }
+            link rel="canonical" href=(canonical);
+            meta http-equiv="refresh" content={ "0; url=" (href) };
+        }
+        body {
+            a href=(href) { (href) }
+        }
+    }
+}
+
 fn inline_css(level: u8) -> impl maud::Render {
     fn gen_inline_css(level: u8) -> String {
         let mut buffer = String::new();
@@ -245,6 +700,14 @@ fn inline_css(level: u8) -> impl maud::Render {
     })
 }
 
+/// Makes the url of a page, it's absolute if the base url is known.
+fn page_url(href: &str, level: u8, base_url: Option<&str>) -> String {
+    match base_url {
+        Some(base_url) => format!("{}/{href}", base_url.trim_end_matches('/')),
+        None => maud::Render::render(&relative_path(href, level)).into_string(),
+    }
+}
+
 fn relative_path(base: &str, level: u8) -> impl maud::Render {
     struct Rel<'base>(&'base str, u8);
 
@@ -262,23 +725,158 @@ fn relative_path(base: &str, level: u8) -> impl maud::Render {
     Rel(base, level)
 }
 
+fn options() -> Options {
+    Options::ENABLE_GFM
+        | Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS
+        | Options::ENABLE_HEADING_ATTRIBUTES
+}
+
+/// Returns the `+++` delimited front matter of the markdown document.
+pub fn front_matter(md: &str) -> Option<CowStr<'_>> {
+    let mut parser = Parser::new_ext(md, options());
+    let Some(Event::Start(Tag::MetadataBlock(_))) = parser.next() else {
+        return None;
+    };
+
+    match parser.next() {
+        Some(Event::Text(s)) => Some(s),
+        _ => Some(CowStr::Borrowed("")),
+    }
+}
+
 pub fn escape(s: &str, output: &mut String) {
     // don't reinvent the wheel,
     // the `maud` already has an implementation of escaping
     _ = maud::Escaper::new(output).write_str(s);
 }
 
-fn md_to_html(md: &str, deps: &mut HashSet<Box<str>>) -> String {
+/// The rendered markdown article.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rendered {
+    pub content: String,
+    /// The plain text of the article without code blocks and inline code.
+    pub text: String,
+    /// Files the article refers to.
+    pub deps: Vec<Box<str>>,
+    /// The first image of the article.
+    pub image: Option<Box<str>>,
+    /// The plain text before the `<!-- more -->` marker or of the first paragraph.
+    pub excerpt: String,
+    /// The number of words outside of code blocks.
+    pub words: usize,
+    /// The number of lines in code blocks.
+    pub code_lines: usize,
+    /// The table of contents if it isn't placed by the `[toc]` marker.
+    pub toc: String,
+    /// Warnings of the rendering, they're reported again on cached builds.
+    pub warnings: Vec<String>,
+}
+
+impl Rendered {
+    /// Code is read slower than text, so a line of it weighs as several words.
+    const CODE_LINE_WORDS: usize = 4;
+
+    /// Estimates the reading time in minutes, it's one minute at least.
+    pub fn minutes(&self, wpm: u32) -> u32 {
+        let words = self.words + self.code_lines * Self::CODE_LINE_WORDS;
+        let minutes = words.div_ceil(wpm.max(1) as usize).max(1);
+        u32::try_from(minutes).unwrap_or(u32::MAX)
+    }
+}
+
+/// The marker separating the excerpt from the rest of the article.
+const MORE: &str = "<!-- more -->";
+
+/// The marker replaced by the table of contents.
+const TOC: &str = "[toc]";
+
+fn is_more(s: &str) -> bool {
+    s.trim() == MORE
+}
+
+/// Checks the id is of a footnote or its reference.
+fn is_footnote_id(id: &str) -> bool {
+    let number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if let Some(n) = id.strip_prefix("fn-") {
+        number(n)
+    } else if let Some(nr) = id.strip_prefix("fnref-") {
+        nr.split_once('-')
+            .is_some_and(|(n, r)| number(n) && number(r))
+    } else {
+        false
+    }
+}
+
+/// Checks the article has the marker as a html block, so not inside code.
+fn has_more(md: &str) -> bool {
+    Parser::new_ext(md, options())
+        .into_offset_iter()
+        .any(|(event, range)| matches!(event, Event::Start(Tag::HtmlBlock)) && is_more(&md[range]))
+}
+
+/// Renders the markdown article to html.
+///
+/// Titles of alerts are localized.
+///
+/// Returns all unsupported constructs found in the article as an error.
+pub fn md_to_html(md: &str, path: &Path, l: Localizer<'_>) -> Result<Rendered, Vec<Unsupported>> {
     let mut html = String::new();
+    let mut text = String::new();
+    let mut deps = BTreeSet::new();
+    let mut image = None;
+    let mut excerpt = String::new();
+    let marked = has_more(md);
+    let mut excerpting = marked;
+    let mut words = 0;
+    let mut code_lines = 0;
+    let mut code_block = false;
+    let mut headings = Headings::default();
+    let mut paragraph = 0;
+    let mut toc_marker: Option<CowStr<'_>> = None;
+    let mut toc_at = None;
+    let mut warnings = vec![];
+    let mut unsupported = vec![];
     let mut code = None;
+    let mut table = Table::default();
+    let mut footnotes = Footnotes::default();
+    let mut footnote = None;
+    let mut metadata = false;
 
-    for event in Parser::new(md) {
+    let parser = Parser::new_ext(md, options()).into_offset_iter();
+    for (event, range) in TextMergeWithOffset::new(parser) {
+        let mut unsupported = |construct| unsupported.push((construct, range.start));
         match event {
-            Event::Start(Tag::Paragraph) => html.push_str("<p>"),
-            Event::Start(Tag::Heading { level, .. }) => _ = write!(&mut html, "<{level}>"),
-            Event::Start(Tag::BlockQuote(_)) => todo!(),
-            Event::Start(Tag::CodeBlock(CodeBlockKind::Indented)) => html.push_str("<pre><code>"),
+            Event::Start(Tag::Paragraph) => {
+                // the first paragraph with some text is the excerpt
+                if !marked && excerpt.trim().is_empty() {
+                    excerpting = true;
+                }
+
+                paragraph = html.len();
+                html.push_str("<p>");
+            }
+            Event::Start(Tag::Heading { id, classes, .. }) => {
+                headings.open(html.len(), id, &classes);
+            }
+            Event::Start(Tag::BlockQuote(None)) => html.push_str("<blockquote>"),
+            Event::Start(Tag::BlockQuote(Some(kind))) => {
+                let (class, title) = alert(kind);
+                _ = write!(
+                    &mut html,
+                    "<blockquote class=\"alert {class}\"><p class=\"alert-title\">",
+                );
+
+                escape(l.alert(class, title), &mut html);
+                html.push_str("</p>");
+            }
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Indented)) => {
+                code_block = true;
+                html.push_str("<pre><code>");
+            }
             Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(s))) => {
+                code_block = true;
                 if &*s == "rust" {
                     html.push_str("<pre><code>");
                     let len = html.len();
@@ -287,40 +885,100 @@ fn md_to_html(md: &str, deps: &mut HashSet<Box<str>>) -> String {
                     html.push_str("<pre><code>");
                 }
             }
-            Event::Start(Tag::HtmlBlock) => todo!(),
+            Event::Start(Tag::HtmlBlock) if is_more(&md[range.clone()]) => excerpting = false,
+            Event::Start(Tag::HtmlBlock) => unsupported("html block"),
             Event::Start(Tag::List(_)) => html.push_str("<ul>"),
             Event::Start(Tag::Item) => html.push_str("<li>"),
-            Event::Start(Tag::FootnoteDefinition(_)) => todo!(),
-            Event::Start(Tag::DefinitionList) => todo!(),
-            Event::Start(Tag::DefinitionListTitle) => todo!(),
-            Event::Start(Tag::DefinitionListDefinition) => todo!(),
-            Event::Start(Tag::Table(_)) => todo!(),
-            Event::Start(Tag::TableHead) => todo!(),
-            Event::Start(Tag::TableRow) => todo!(),
-            Event::Start(Tag::TableCell) => todo!(),
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                // render the definition separately,
+                // it will be placed at the end of the article
+                let main = mem::take(&mut html);
+                footnote = Some((label, main));
+            }
+            Event::Start(Tag::DefinitionList) => unsupported("definition list"),
+            Event::Start(Tag::DefinitionListTitle) => unsupported("definition list title"),
+            Event::Start(Tag::DefinitionListDefinition) => {
+                unsupported("definition list definition");
+            }
+            Event::Start(Tag::Table(aligns)) => {
+                html.push_str("<div class=\"table\"><table>");
+                table = Table {
+                    aligns,
+                    ..Table::default()
+                };
+            }
+            Event::Start(Tag::TableHead) => {
+                html.push_str("<thead><tr>");
+                table.head = true;
+                table.cell = 0;
+            }
+            Event::Start(Tag::TableRow) => {
+                html.push_str("<tr>");
+                table.cell = 0;
+            }
+            Event::Start(Tag::TableCell) => {
+                let tag = table.cell_tag();
+                match table.align() {
+                    Alignment::None => _ = write!(&mut html, "<{tag}>"),
+                    Alignment::Left => _ = write!(&mut html, "<{tag} class=\"left\">"),
+                    Alignment::Center => _ = write!(&mut html, "<{tag} class=\"center\">"),
+                    Alignment::Right => _ = write!(&mut html, "<{tag} class=\"right\">"),
+                }
+            }
             Event::Start(Tag::Emphasis) => html.push_str("<em>"),
             Event::Start(Tag::Strong) => html.push_str("<strong>"),
-            Event::Start(Tag::Strikethrough) => todo!(),
-            Event::Start(Tag::Superscript) => todo!(),
-            Event::Start(Tag::Subscript) => todo!(),
+            Event::Start(Tag::Strikethrough) => unsupported("strikethrough"),
+            Event::Start(Tag::Superscript) => unsupported("superscript"),
+            Event::Start(Tag::Subscript) => unsupported("subscript"),
             Event::Start(Tag::Link { dest_url, .. }) => {
                 _ = write!(&mut html, "<a href=\"{dest_url}\" target=\"_blank\">");
             }
             Event::Start(Tag::Image { dest_url, .. }) => {
                 _ = write!(&mut html, "<img src=\"../{dest_url}\">");
                 deps.insert(Box::from(&*dest_url));
+                image.get_or_insert_with(|| Box::from(&*dest_url));
+            }
+            Event::Start(Tag::MetadataBlock(_)) => metadata = true,
+            Event::End(TagEnd::Paragraph) => {
+                if excerpting {
+                    excerpt.push(' ');
+                    excerpting = marked;
+                }
+
+                let marker = toc_marker.take();
+                if marker.is_some() && toc_at.is_none() && html.len() == paragraph + "<p>".len() {
+                    // the marker paragraph is replaced by the table of contents
+                    html.truncate(paragraph);
+                    toc_at = Some(paragraph);
+                } else {
+                    if let Some(marker) = marker {
+                        // it's a text starting with the marker, so keep it
+                        let mut text = String::new();
+                        escape(&marker, &mut text);
+                        html.insert_str(paragraph + "<p>".len(), &text);
+                    }
+
+                    html.push_str("</p>");
+                }
+            }
+            Event::End(TagEnd::Heading(level)) => {
+                if excerpting {
+                    excerpt.push(' ');
+                }
+
+                if let Some(id) = headings.close(level, &mut html) {
+                    warnings.push(format!("duplicate id {id:?} in {}", path.display()));
+                }
             }
-            Event::Start(Tag::MetadataBlock(_)) => todo!(),
-            Event::End(TagEnd::Paragraph) => html.push_str("</p>"),
-            Event::End(TagEnd::Heading(level)) => _ = write!(&mut html, "</{level}>"),
-            Event::End(TagEnd::BlockQuote(_)) => todo!(),
+            Event::End(TagEnd::BlockQuote(_)) => html.push_str("</blockquote>"),
             Event::End(TagEnd::CodeBlock) => {
+                code_block = false;
                 if let Some((start, end)) = code.take() {
                     let src = &html[start..end];
                     let src = match rust::highlight(src) {
                         Ok(src) => src,
                         Err(e) => {
-                            eprintln!("highlight rust error: {e}");
+                            warnings.push(format!("highlight rust error: {e}"));
                             src.to_owned()
                         }
                     };
@@ -331,49 +989,670 @@ fn md_to_html(md: &str, deps: &mut HashSet<Box<str>>) -> String {
 
                 html.push_str("</code></pre>");
             }
-            Event::End(TagEnd::HtmlBlock) => todo!(),
+            Event::End(TagEnd::HtmlBlock) => {}
             Event::End(TagEnd::List(_)) => html.push_str("</ul>"),
             Event::End(TagEnd::Item) => html.push_str("</li>"),
-            Event::End(TagEnd::FootnoteDefinition) => todo!(),
-            Event::End(TagEnd::DefinitionList) => todo!(),
-            Event::End(TagEnd::DefinitionListTitle) => todo!(),
-            Event::End(TagEnd::DefinitionListDefinition) => todo!(),
-            Event::End(TagEnd::Table) => todo!(),
-            Event::End(TagEnd::TableHead) => todo!(),
-            Event::End(TagEnd::TableRow) => todo!(),
-            Event::End(TagEnd::TableCell) => todo!(),
+            Event::End(TagEnd::FootnoteDefinition) => {
+                if let Some((label, main)) = footnote.take() {
+                    let def = mem::replace(&mut html, main);
+                    footnotes.define(&label, def);
+                }
+            }
+            Event::End(TagEnd::DefinitionList) => {}
+            Event::End(TagEnd::DefinitionListTitle) => {}
+            Event::End(TagEnd::DefinitionListDefinition) => {}
+            Event::End(TagEnd::Table) => html.push_str("</tbody></table></div>"),
+            Event::End(TagEnd::TableHead) => {
+                html.push_str("</tr></thead><tbody>");
+                table.head = false;
+            }
+            Event::End(TagEnd::TableRow) => html.push_str("</tr>"),
+            Event::End(TagEnd::TableCell) => {
+                _ = write!(&mut html, "</{}>", table.cell_tag());
+                table.cell += 1;
+            }
             Event::End(TagEnd::Emphasis) => html.push_str("</em>"),
             Event::End(TagEnd::Strong) => html.push_str("</strong>"),
-            Event::End(TagEnd::Strikethrough) => todo!(),
-            Event::End(TagEnd::Superscript) => todo!(),
-            Event::End(TagEnd::Subscript) => todo!(),
+            Event::End(TagEnd::Strikethrough) => {}
+            Event::End(TagEnd::Superscript) => {}
+            Event::End(TagEnd::Subscript) => {}
             Event::End(TagEnd::Link) => html.push_str("</a>"),
             Event::End(TagEnd::Image) => html.push_str("</img>"),
-            Event::End(TagEnd::MetadataBlock(_)) => todo!(),
+            Event::End(TagEnd::MetadataBlock(_)) => metadata = false,
+            Event::Text(_) if metadata => {}
+            // footnotes are rendered apart, so the marker is taken from the main text only
+            Event::Text(s)
+                if s.trim() == TOC
+                    && footnote.is_none()
+                    && html.len() == paragraph + "<p>".len() =>
+            {
+                toc_marker = Some(s);
+            }
             Event::Text(s) => {
+                if code_block {
+                    code_lines += s.lines().count();
+                } else {
+                    words += s.split_whitespace().count();
+                }
+
                 if let Some((start, _)) = code {
                     html.push_str(&s);
                     code = Some((start, html.len()));
                 } else {
                     escape(&s, &mut html);
                 }
+
+                if !code_block {
+                    for label in undefined_footnotes(&s) {
+                        warnings.push(format!(
+                            "undefined footnote [^{label}] in {}",
+                            path.display(),
+                        ));
+                    }
+
+                    headings.push_text(&s);
+                    text.push_str(&s);
+                    text.push(' ');
+                    if excerpting {
+                        excerpt.push_str(&s);
+                    }
+                }
             }
             Event::Code(s) => {
+                words += s.split_whitespace().count();
+                headings.push_text(&s);
+                if excerpting {
+                    excerpt.push_str(&s);
+                }
+
                 html.push_str("<code class=\"inline\">");
                 escape(&s, &mut html);
                 html.push_str("</code>");
             }
-            Event::InlineMath(_) => todo!(),
-            Event::DisplayMath(_) => todo!(),
+            Event::InlineMath(_) => unsupported("inline math"),
+            Event::DisplayMath(_) => unsupported("display math"),
+            Event::Html(s) if is_more(&s) => {}
             Event::Html(s) => html.push_str(&s),
             Event::InlineHtml(s) => html.push_str(&s),
-            Event::FootnoteReference(_) => todo!(),
-            Event::SoftBreak => html.push_str("<br>"),
-            Event::HardBreak => todo!(),
-            Event::Rule => todo!(),
-            Event::TaskListMarker(_) => todo!(),
+            Event::FootnoteReference(label) => {
+                let (n, r) = footnotes.reference(&label);
+                _ = write!(
+                    &mut html,
+                    "<sup class=\"footnote-ref\"><a id=\"fnref-{n}-{r}\" href=\"#fn-{n}\">{n}</a></sup>",
+                );
+            }
+            Event::SoftBreak => {
+                if excerpting {
+                    excerpt.push(' ');
+                }
+
+                html.push_str("<br>");
+            }
+            Event::HardBreak => unsupported("hard break"),
+            Event::Rule => unsupported("rule"),
+            Event::TaskListMarker(_) => unsupported("task list marker"),
+        }
+    }
+
+    if !unsupported.is_empty() {
+        let unsupported = unsupported
+            .into_iter()
+            .map(|(construct, offset)| Unsupported::new(path, md, offset, construct))
+            .collect();
+
+        return Err(unsupported);
+    }
+
+    let mut toc = headings.toc();
+    if let Some(at) = toc_at {
+        html.insert_str(at, &mem::take(&mut toc));
+    }
+
+    for label in footnotes.render(&mut html) {
+        warnings.push(format!(
+            "undefined footnote [^{label}] in {}",
+            path.display(),
+        ));
+    }
+
+    Ok(Rendered {
+        content: html,
+        text,
+        deps: deps.into_iter().collect(),
+        image,
+        excerpt: excerpt.split_whitespace().collect::<Vec<_>>().join(" "),
+        words,
+        code_lines,
+        toc,
+        warnings,
+    })
+}
+
+/// Trims the excerpt to the length in chars on a word boundary.
+pub fn trim_excerpt(excerpt: &str, len: usize) -> String {
+    if excerpt.chars().count() <= len {
+        return excerpt.to_owned();
+    }
+
+    let mut trimmed = String::new();
+    for word in excerpt.split(' ') {
+        let sep = usize::from(!trimmed.is_empty());
+        if trimmed.chars().count() + sep + word.chars().count() + 1 > len {
+            break;
+        }
+
+        if sep == 1 {
+            trimmed.push(' ');
+        }
+
+        trimmed.push_str(word);
+    }
+
+    trimmed.push('…');
+    trimmed
+}
+
+/// A markdown construct the renderer can't handle yet.
+pub struct Unsupported {
+    path: Box<Path>,
+    line: usize,
+    column: usize,
+    offset: usize,
+    construct: &'static str,
+}
+
+impl Unsupported {
+    fn new(path: &Path, md: &str, offset: usize, construct: &'static str) -> Self {
+        let before = &md[..offset];
+        let line_start = before.rfind('\n').map_or(0, |n| n + 1);
+        Self {
+            path: Box::from(path),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            offset,
+            construct,
+        }
+    }
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            path,
+            line,
+            column,
+            offset,
+            construct,
+        } = self;
+
+        write!(
+            f,
+            "{}:{line}:{column}: unsupported {construct} (at byte {offset})",
+            path.display(),
+        )
+    }
+}
+
+/// Footnotes by their labels, matched case-insensitively like the parser does.
+#[derive(Default)]
+struct Footnotes {
+    notes: Vec<Note>,
+    defs: HashMap<String, String>,
+}
+
+impl Footnotes {
+    /// Registers a reference to the footnote.
+    ///
+    /// Returns the footnote number in order of first reference
+    /// and the number of this reference to the footnote.
+    fn reference(&mut self, label: &str) -> (usize, usize) {
+        let label = label.to_lowercase();
+        let n = match self.notes.iter().position(|note| note.label == label) {
+            Some(n) => n,
+            None => {
+                self.notes.push(Note { label, refs: 0 });
+                self.notes.len() - 1
+            }
+        };
+
+        let note = &mut self.notes[n];
+        note.refs += 1;
+        (n + 1, note.refs)
+    }
+
+    fn define(&mut self, label: &str, def: String) {
+        self.defs.insert(label.to_lowercase(), def);
+    }
+
+    /// Renders the footnotes section.
+    ///
+    /// Returns labels of referenced footnotes without definitions.
+    fn render(mut self, html: &mut String) -> Vec<String> {
+        let mut undefined = vec![];
+        if self.notes.is_empty() {
+            return undefined;
+        }
+
+        html.push_str("<section class=\"footnotes\"><ol>");
+        for (n, Note { label, refs }) in iter::zip(1.., self.notes) {
+            let mut def = match self.defs.remove(&label) {
+                Some(def) => def,
+                None => {
+                    undefined.push(label);
+                    String::new()
+                }
+            };
+
+            // place back references inside the last paragraph
+            let p = def.strip_suffix("</p>").map(str::len);
+            let tail = def.split_off(p.unwrap_or(def.len()));
+
+            _ = write!(html, "<li id=\"fn-{n}\">{def}");
+            for r in 1..=refs {
+                _ = write!(
+                    html,
+                    " <a class=\"footnote-back\" href=\"#fnref-{n}-{r}\">↩</a>",
+                );
+            }
+
+            _ = write!(html, "{tail}</li>");
+        }
+
+        html.push_str("</ol></section>");
+        undefined
+    }
+}
+
+struct Note {
+    label: String,
+    refs: usize,
+}
+
+/// Headings of the article with unique ids.
+#[derive(Default)]
+struct Headings {
+    ids: BTreeSet<String>,
+    list: Vec<Heading>,
+    open: Option<OpenHeading>,
+}
+
+struct Heading {
+    level: HeadingLevel,
+    id: String,
+    text: String,
+}
+
+struct OpenHeading {
+    /// The position of the opening tag in the html.
+    start: usize,
+    id: Option<String>,
+    classes: String,
+    text: String,
+}
+
+impl Headings {
+    fn open(&mut self, start: usize, id: Option<CowStr<'_>>, classes: &[CowStr<'_>]) {
+        self.open = Some(OpenHeading {
+            start,
+            id: id.map(|id| id.into_string()),
+            classes: classes.join(" "),
+            text: String::new(),
+        });
+    }
+
+    fn push_text(&mut self, s: &str) {
+        if let Some(open) = &mut self.open {
+            open.text.push_str(s);
+        }
+    }
+
+    /// Closes the heading inserting its opening tag with an id and an anchor link.
+    ///
+    /// Returns the explicit id if it's already used by another heading or a footnote.
+    fn close(&mut self, level: HeadingLevel, html: &mut String) -> Option<String> {
+        let OpenHeading {
+            start,
+            id,
+            classes,
+            text,
+        } = self.open.take()?;
+
+        let taken = |id: &String| self.ids.contains(id) || is_footnote_id(id);
+
+        // the explicit id is kept as is, a generated one is deduplicated
+        let duplicate = id.as_ref().filter(|id| taken(id)).cloned();
+        let id = id.unwrap_or_else(|| {
+            let slug = match slug(&text) {
+                slug if slug.is_empty() => String::from("section"),
+                slug => slug,
+            };
+
+            let mut id = slug.clone();
+            for n in 1.. {
+                if !taken(&id) {
+                    break;
+                }
+
+                id = format!("{slug}-{n}");
+            }
+
+            id
+        });
+
+        let mut tag = format!("<{level} id=\"");
+        escape(&id, &mut tag);
+        tag.push('"');
+        if !classes.is_empty() {
+            tag.push_str(" class=\"");
+            escape(&classes, &mut tag);
+            tag.push('"');
         }
+
+        tag.push('>');
+        html.insert_str(start, &tag);
+
+        html.push_str("<a class=\"anchor\" href=\"#");
+        escape(&id, html);
+        _ = write!(html, "\" aria-hidden=\"true\">#</a></{level}>");
+
+        self.ids.insert(id.clone());
+        self.list.push(Heading {
+            level,
+            id,
+            text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+        });
+
+        duplicate
+    }
+
+    /// Renders the table of contents, it's empty if there are no headings.
+    fn toc(&self) -> String {
+        if self.list.is_empty() {
+            return String::new();
+        }
+
+        let mut html = String::from("<nav class=\"toc\"><ul>");
+        for Heading { level, id, text } in &self.list {
+            _ = write!(html, "<li class=\"toc-{level}\"><a href=\"#");
+            escape(id, &mut html);
+            html.push_str("\">");
+            escape(text, &mut html);
+            html.push_str("</a></li>");
+        }
+
+        html.push_str("</ul></nav>");
+        html
+    }
+}
+
+/// Finds footnote references like `[^label]` left in a text,
+/// the parser reports a reference as plain text when it has no definition.
+fn undefined_footnotes(s: &str) -> impl Iterator<Item = &str> {
+    s.match_indices("[^").filter_map(|(start, _)| {
+        let rest = &s[start + 2..];
+        let end = rest.find(']')?;
+        let label = &rest[..end];
+        let valid = !label.is_empty() && !label.contains(char::is_whitespace);
+        valid.then_some(label)
+    })
+}
+
+#[derive(Default)]
+struct Table {
+    aligns: Vec<Alignment>,
+    head: bool,
+    cell: usize,
+}
+
+impl Table {
+    fn cell_tag(&self) -> &'static str {
+        if self.head { "th" } else { "td" }
+    }
+
+    fn align(&self) -> Alignment {
+        self.aligns
+            .get(self.cell)
+            .copied()
+            .unwrap_or(Alignment::None)
+    }
+}
+
+fn alert(kind: BlockQuoteKind) -> (&'static str, &'static str) {
+    match kind {
+        BlockQuoteKind::Note => ("note", "Note"),
+        BlockQuoteKind::Tip => ("tip", "Tip"),
+        BlockQuoteKind::Important => ("important", "Important"),
+        BlockQuoteKind::Warning => ("warning", "Warning"),
+        BlockQuoteKind::Caution => ("caution", "Caution"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::lang::Local};
+
+    fn render(md: &str) -> Result<Rendered, Vec<Unsupported>> {
+        let lang = Lang::from_ascii(*b"en").expect("valid lang");
+        md_to_html(md, Path::new("test.md"), Local::new().bind(lang))
+    }
+
+    fn to_html(md: &str) -> String {
+        render(md)
+            .map(|rendered| rendered.content)
+            .unwrap_or_default()
     }
 
-    html
+    #[test]
+    fn slugs() {
+        assert_eq!(slug("Rust"), "rust");
+        assert_eq!(slug("  Web  dev!"), "web-dev");
+        assert_eq!(slug("Привет, мир"), "привет-мир");
+        assert_eq!(slug("!!"), "");
+    }
+
+    #[test]
+    fn excerpts() {
+        let excerpt = |md| render(md).map(|rendered| rendered.excerpt);
+        assert_eq!(
+            excerpt("## Title\n\nThe *first*\n`para`.\n\nThe second.").ok(),
+            Some(String::from("The first para.")),
+        );
+
+        assert_eq!(
+            excerpt("The first.\n\nThe second.\n<!-- more -->\nThe third.").ok(),
+            Some(String::from("The first. The second.")),
+        );
+
+        assert_eq!(
+            excerpt("The first.\n\n```html\n<!-- more -->\n```\n\nThe second.").ok(),
+            Some(String::from("The first.")),
+        );
+
+        assert_eq!(trim_excerpt("a short one", 20), "a short one");
+        assert_eq!(trim_excerpt("the long excerpt", 10), "the long…");
+    }
+
+    #[test]
+    fn warnings() {
+        let md = "Text [^x]\n\n```sh\ngrep [^a-z]\n```";
+        let rendered = render(md).ok().expect("rendered");
+        assert_eq!(rendered.warnings, ["undefined footnote [^x] in test.md"]);
+    }
+
+    #[test]
+    fn text_without_code() {
+        let md = "Some text\n\n```sh\ngrep secret\n```\n\n    indented\n";
+        let rendered = render(md).ok().expect("rendered");
+        assert_eq!(rendered.text.trim(), "Some text");
+        assert_eq!(rendered.excerpt, "Some text");
+    }
+
+    #[test]
+    fn reading_time() {
+        let md = "Some words `and code`\n\n```\nlet a = 1;\nlet b = 2;\n```";
+        let rendered = render(md).ok().expect("rendered");
+        assert_eq!((rendered.words, rendered.code_lines), (4, 2));
+        assert_eq!(rendered.minutes(200), 1);
+        assert_eq!(rendered.minutes(5), 3);
+    }
+
+    #[test]
+    fn headings() {
+        let anchor = |id| format!("<a class=\"anchor\" href=\"#{id}\" aria-hidden=\"true\">#</a>");
+        assert_eq!(
+            to_html("## Привет, `мир`\n## Привет мир\n## Own {#own .wide}"),
+            format!(
+                "<h2 id=\"привет-мир\">Привет, <code class=\"inline\">мир</code>{}</h2>\
+                <h2 id=\"привет-мир-1\">Привет мир{}</h2>\
+                <h2 id=\"own\" class=\"wide\">Own{}</h2>",
+                anchor("привет-мир"),
+                anchor("привет-мир-1"),
+                anchor("own"),
+            ),
+        );
+
+        let md = "## A {#b}\n## B\n## B {#b}\n## C {#fn-1}\n## Fn 1";
+        let rendered = render(md).ok().expect("rendered");
+        let ids: Vec<_> = (rendered.toc.split("href=\"#").skip(1))
+            .filter_map(|s| s.split_once('"'))
+            .map(|(id, _)| id)
+            .collect();
+
+        assert_eq!(ids, ["b", "b-1", "b", "fn-1", "fn-1-1"]);
+        assert_eq!(
+            rendered.warnings,
+            [
+                "duplicate id \"b\" in test.md",
+                "duplicate id \"fn-1\" in test.md",
+            ],
+        );
+    }
+
+    #[test]
+    fn toc_marker() {
+        let rendered = render("[toc]\n\n## A\n### B").ok().expect("rendered");
+
+        assert!(
+            rendered.content.starts_with(
+                "<nav class=\"toc\"><ul><li class=\"toc-h2\"><a href=\"#a\">A</a></li>\
+                <li class=\"toc-h3\"><a href=\"#b\">B</a></li></ul></nav><h2 id=\"a\">"
+            ),
+            "the marker is replaced",
+        );
+
+        assert!(rendered.toc.is_empty(), "the toc is placed by the marker");
+
+        assert!(
+            to_html("[toc] *x*").starts_with("<p>[toc] <em>x</em></p>"),
+            "the text is kept",
+        );
+
+        let rendered = render("A[^1].\n\n[^1]: A long footnote.\n\n    [toc]\n\n## B")
+            .ok()
+            .expect("rendered");
+
+        assert!(
+            rendered.content.starts_with("<p>A<sup"),
+            "the marker of a footnote is kept",
+        );
+
+        assert!(
+            !rendered.toc.is_empty(),
+            "the toc isn't placed by the footnote"
+        );
+    }
+
+    #[test]
+    fn page_urls() {
+        assert_eq!(page_url("en/a.html", 1, None), "../en/a.html");
+        assert_eq!(
+            page_url("en/a.html", 1, Some("https://example.com/")),
+            "https://example.com/en/a.html",
+        );
+    }
+
+    #[test]
+    fn blockquote() {
+        assert_eq!(
+            to_html("> a\n>\n> > b"),
+            "<blockquote><p>a</p><blockquote><p>b</p></blockquote></blockquote>",
+        );
+    }
+
+    #[test]
+    fn blockquote_alert() {
+        assert_eq!(
+            to_html("> [!WARNING]\n> a"),
+            "<blockquote class=\"alert warning\"><p class=\"alert-title\">Warning</p><p>a</p></blockquote>",
+        );
+    }
+
+    #[test]
+    fn table() {
+        assert_eq!(
+            to_html("| a | b |\n|:--|--:|\n| c | d |"),
+            "<div class=\"table\"><table>\
+            <thead><tr><th class=\"left\">a</th><th class=\"right\">b</th></tr></thead>\
+            <tbody><tr><td class=\"left\">c</td><td class=\"right\">d</td></tr></tbody>\
+            </table></div>",
+        );
+    }
+
+    #[test]
+    fn footnotes() {
+        assert_eq!(
+            to_html("[^b] [^a] [^b]\n\n[^a]: x\n\n[^b]: y"),
+            "<p>\
+            <sup class=\"footnote-ref\"><a id=\"fnref-1-1\" href=\"#fn-1\">1</a></sup> \
+            <sup class=\"footnote-ref\"><a id=\"fnref-2-1\" href=\"#fn-2\">2</a></sup> \
+            <sup class=\"footnote-ref\"><a id=\"fnref-1-2\" href=\"#fn-1\">1</a></sup>\
+            </p>\
+            <section class=\"footnotes\"><ol>\
+            <li id=\"fn-1\"><p>y \
+            <a class=\"footnote-back\" href=\"#fnref-1-1\">↩</a> \
+            <a class=\"footnote-back\" href=\"#fnref-1-2\">↩</a></p></li>\
+            <li id=\"fn-2\"><p>x <a class=\"footnote-back\" href=\"#fnref-2-1\">↩</a></p></li>\
+            </ol></section>",
+        );
+
+        let rendered = render("See[^Note].\n\n[^note]: The definition.")
+            .ok()
+            .expect("rendered");
+
+        assert!(
+            rendered
+                .content
+                .contains("<li id=\"fn-1\"><p>The definition. <a"),
+            "labels are case-insensitive",
+        );
+
+        assert!(rendered.warnings.is_empty(), "the footnote is defined");
+    }
+
+    #[test]
+    fn unsupported() {
+        let lang = Lang::from_ascii(*b"en").expect("valid lang");
+        let errors = md_to_html(
+            "a\n\n---\n\nb  \nc",
+            Path::new("a.md"),
+            Local::new().bind(lang),
+        )
+        .err()
+        .unwrap_or_default();
+
+        let actual: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            actual,
+            [
+                "a.md:3:1: unsupported rule (at byte 3)",
+                "a.md:5:2: unsupported hard break (at byte 9)",
+            ],
+        );
+    }
+
+    #[test]
+    fn undefined_footnote() {
+        let actual: Vec<_> = undefined_footnotes("a [^x] [y] [^] [^z]").collect();
+        assert_eq!(actual, ["x", "z"]);
+    }
 }
@@ -3,7 +3,7 @@ use {
     std::fmt,
 };
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Hash)]
 pub enum Icon {
     Bluesky,
     Discord,
@@ -13,6 +13,8 @@ pub enum Icon {
     Bookshelf,
     Date,
     Earth,
+    Tag,
+    Clock,
 }
 
 impl Icon {
@@ -26,6 +28,8 @@ impl Icon {
             "bo" => Ok(Self::Bookshelf),
             "dt" => Ok(Self::Date),
             "ea" => Ok(Self::Earth),
+            "tg" => Ok(Self::Tag),
+            "cl" => Ok(Self::Clock),
             _ => Err(UnknownIcon),
         }
     }
@@ -40,6 +44,8 @@ impl Icon {
             Self::Bookshelf => include_str!("../icons/bookshelf.svg"),
             Self::Date => include_str!("../icons/date.svg"),
             Self::Earth => include_str!("../icons/earth.svg"),
+            Self::Tag => include_str!("../icons/tag.svg"),
+            Self::Clock => include_str!("../icons/clock.svg"),
         }
     }
 
@@ -53,6 +59,8 @@ impl Icon {
             Self::Bookshelf => "Bookshelf",
             Self::Date => "Date",
             Self::Earth => "Earth",
+            Self::Tag => "Tag",
+            Self::Clock => "Clock",
         }
     }
 }
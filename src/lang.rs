@@ -115,9 +115,85 @@ impl<'loc> Localizer<'loc> {
         &payload.articles
     }
 
+    pub fn search(&self) -> &'loc str {
+        let Some(payload) = self.local.get(self.lang) else {
+            return "";
+        };
+
+        &payload.search
+    }
+
+    pub fn updated(&self) -> &'loc str {
+        let Some(payload) = self.local.get(self.lang) else {
+            return "";
+        };
+
+        &payload.updated
+    }
+
+    pub fn tags(&self) -> &'loc str {
+        let Some(payload) = self.local.get(self.lang) else {
+            return "";
+        };
+
+        &payload.tags
+    }
+
+    /// Returns the localized label of the tag or the tag itself.
+    pub fn tag<'tag>(&self, tag: &'tag str) -> &'tag str
+    where
+        'loc: 'tag,
+    {
+        self.local
+            .get(self.lang)
+            .and_then(|payload| payload.tag.get(tag))
+            .map_or(tag, |label| label)
+    }
+
+    /// Returns the localized title of the alert kind or the default title.
+    pub fn alert<'title>(&self, kind: &str, title: &'title str) -> &'title str
+    where
+        'loc: 'title,
+    {
+        self.local
+            .get(self.lang)
+            .and_then(|payload| payload.alert.get(kind))
+            .map_or(title, |title| title)
+    }
+
+    /// Returns the reading speed in words per minute.
+    pub fn wpm(&self) -> u32 {
+        self.local
+            .get(self.lang)
+            .map_or_else(Payload::wpm, |payload| payload.wpm)
+    }
+
+    /// Returns the localized reading time or an empty string.
+    pub fn reading(&self, minutes: u32) -> String {
+        let Some(payload) = self.local.get(self.lang) else {
+            return String::new();
+        };
+
+        let form = payload.reading.form(plural(self.lang, minutes));
+        form.replace("{}", &minutes.to_string())
+    }
+
+    /// Returns the `language_TERRITORY` locale if it's set.
+    pub fn locale(&self) -> Option<&'loc str> {
+        self.local
+            .get(self.lang)
+            .map(|payload| &*payload.locale)
+            .filter(|locale| !locale.is_empty())
+    }
+
     pub fn lang(self) -> Lang {
         self.lang
     }
+
+    /// Binds the same localization to another language.
+    pub fn bind(self, lang: Lang) -> Self {
+        Self { lang, ..self }
+    }
 }
 
 #[derive(Deserialize)]
@@ -141,4 +217,120 @@ impl Local {
 struct Payload {
     articles: Box<str>,
     month: [Box<str>; 12],
+    /// The locale of Open Graph previews, like `en_US`.
+    #[serde(default)]
+    locale: Box<str>,
+    #[serde(default)]
+    search: Box<str>,
+    #[serde(default)]
+    updated: Box<str>,
+    #[serde(default)]
+    tags: Box<str>,
+    #[serde(default)]
+    tag: HashMap<Box<str>, Box<str>>,
+    /// Titles of alerts by their kind, like `note`.
+    #[serde(default)]
+    alert: HashMap<Box<str>, Box<str>>,
+    /// Words read per minute.
+    #[serde(default = "Payload::wpm")]
+    wpm: u32,
+    /// The reading time where `{}` is replaced by minutes.
+    #[serde(default)]
+    reading: Plural,
+}
+
+impl Payload {
+    fn wpm() -> u32 {
+        200
+    }
+}
+
+/// Plural forms of a phrase, the `other` one is used if a form is empty.
+#[derive(Default, Deserialize)]
+struct Plural {
+    #[serde(default)]
+    one: Box<str>,
+    #[serde(default)]
+    few: Box<str>,
+    #[serde(default)]
+    many: Box<str>,
+    #[serde(default)]
+    other: Box<str>,
+}
+
+impl Plural {
+    fn form(&self, form: Form) -> &str {
+        let s = match form {
+            Form::One => &self.one,
+            Form::Few => &self.few,
+            Form::Many => &self.many,
+            Form::Other => &self.other,
+        };
+
+        if s.is_empty() { &self.other } else { s }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Form {
+    One,
+    Few,
+    Many,
+    Other,
+}
+
+/// Selects the plural form of the number.
+///
+/// East Slavic languages have one, few and many forms,
+/// the rest are assumed to have one and other ones.
+fn plural(lang: Lang, n: u32) -> Form {
+    match lang.as_str() {
+        "ru" | "uk" | "be" => match (n % 10, n % 100) {
+            (1, 11) | (2..=4, 12..=14) => Form::Many,
+            (1, _) => Form::One,
+            (2..=4, _) => Form::Few,
+            _ => Form::Many,
+        },
+        _ if n == 1 => Form::One,
+        _ => Form::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plurals() {
+        let lang = |s| Lang::from_str(s).ok().expect("valid lang");
+        let forms = |l| [1, 2, 5, 11, 12, 21, 22, 25].map(|n| plural(lang(l), n));
+        assert_eq!(
+            forms("ru"),
+            [
+                Form::One,
+                Form::Few,
+                Form::Many,
+                Form::Many,
+                Form::Many,
+                Form::One,
+                Form::Few,
+                Form::Many,
+            ],
+        );
+
+        assert_eq!(forms("en")[..2], [Form::One, Form::Other]);
+    }
+
+    #[test]
+    fn alerts() {
+        let months = ["\"\""; 12].join(", ");
+        let local: Local = toml::from_str(&format!(
+            "[ru]\narticles = \"\"\nmonth = [{months}]\nalert = {{ note = \"Заметка\" }}",
+        ))
+        .expect("valid local");
+
+        let l = local.bind(Lang::from_str("ru").ok().expect("valid lang"));
+        assert_eq!(l.alert("note", "Note"), "Заметка");
+        assert_eq!(l.alert("tip", "Tip"), "Tip");
+    }
 }
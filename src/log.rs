@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+/// How much the generator reports about its work.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Normal as u8);
+static WARNINGS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    LEVEL.load(Ordering::Relaxed) >= level as u8
+}
+
+pub fn count_warning() {
+    WARNINGS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns the number of warnings since the last call.
+pub fn take_warnings() -> usize {
+    WARNINGS.swap(0, Ordering::Relaxed)
+}
+
+/// Prints a progress message unless the output is quiet.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Level::Normal) {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Prints a detailed message only if the output is verbose.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Level::Verbose) {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Prints a warning and counts it for the build summary.
+macro_rules! warning {
+    ($($arg:tt)*) => {{
+        $crate::log::count_warning();
+        eprintln!("warning: {}", format_args!($($arg)*));
+    }};
+}
+
+pub(crate) use {debug, info, warning};
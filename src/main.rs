@@ -1,39 +1,97 @@
+mod cache;
+mod cli;
 mod date;
+mod feed;
 mod html;
 mod icon;
 mod lang;
+mod log;
+mod meta;
 mod rust;
+mod search;
+mod serve;
+mod sitemap;
 
 use {
     crate::{
+        cache::Cache,
+        cli::{Build, Cli, Command},
         date::Date,
-        html::{Make, Post, Target, Translation},
+        feed::Feed,
+        html::{Feeds, Make, Post, TagPosts, Target, Translation, Unsupported},
         icon::Icon,
         lang::{Lang, Local},
+        log::{debug, info, warning},
+        meta::{ArticleMeta, Meta},
     },
-    serde::{Deserialize, Serialize},
+    serde::Deserialize,
     std::{
         cmp::Reverse,
-        collections::{BTreeMap, BTreeSet, HashMap, HashSet},
-        fs,
+        collections::{BTreeMap, BTreeSet},
+        env, fs,
         io::{Error, ErrorKind},
-        path::Path,
+        iter,
+        path::{Path, PathBuf},
         process::ExitCode,
+        time::Instant,
     },
 };
 
 fn main() -> ExitCode {
-    if let Err(e) = run() {
+    let Cli { command, level } = match cli::parse(env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{}", cli::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    log::set_level(level);
+    match command {
+        Command::Build(build) => {
+            let start = Instant::now();
+            let res = run(&build, false, &mut vec![]);
+            summary(start, res)
+        }
+        Command::Serve(build, port) => {
+            if let Err(e) = serve::serve(build, port) {
+                eprintln!("error: {e}");
+                ExitCode::FAILURE
+            } else {
+                ExitCode::SUCCESS
+            }
+        }
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+fn summary(start: Instant, res: Result<(), Error>) -> ExitCode {
+    let warnings = match log::take_warnings() {
+        0 => String::new(),
+        1 => String::from(" with 1 warning"),
+        n => format!(" with {n} warnings"),
+    };
+
+    if let Err(e) = res {
         eprintln!("error: {e}");
+        eprintln!("build failed{warnings}");
         ExitCode::FAILURE
     } else {
+        info!("finished in {:.2?}{warnings}", start.elapsed());
         ExitCode::SUCCESS
     }
 }
 
-fn run() -> Result<(), Error> {
-    let conf = read_conf()?;
-    let mut gener = Generator::new(&conf)?;
+/// Generates the blog.
+///
+/// Collects paths of source files the output depends on,
+/// the ones found before a failure are collected too.
+fn run(build: &Build, reload: bool, sources: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let conf = read_conf(build, sources)?;
+    let mut gener = Generator::new(&conf, build, reload)?;
     for (name, info) in &conf.articles {
         let mut generate = gener.generate(name);
         for (&lang, article) in info {
@@ -47,9 +105,15 @@ fn run() -> Result<(), Error> {
         }
     }
 
+    gener.check()?;
     gener.generate_list()?;
-    gener.save()?;
-    Ok(())
+    gener.generate_sitemap()?;
+    gener.generate_tags()?;
+    gener.generate_aliases()?;
+    gener.generate_orphans()?;
+
+    sources.extend(gener.deps.iter().map(|dep| conf.root.join(&**dep)));
+    gener.save()
 }
 
 struct Langs<'it> {
@@ -59,24 +123,43 @@ struct Langs<'it> {
 
 struct Generator<'conf> {
     conf: &'conf Conf,
+    out: &'conf Path,
+    reload: bool,
+    prune: bool,
     meta: Meta,
-    deps: HashSet<Box<str>>,
-    langs: HashSet<Lang>,
-    posts: HashMap<Lang, Vec<Post<'conf>>>,
+    cache: Cache,
+    deps: BTreeSet<Box<str>>,
+    /// Languages with listed articles, only they have list pages.
+    listed: BTreeSet<Lang>,
+    posts: BTreeMap<Lang, Vec<Post<'conf>>>,
+    unsupported: Vec<Unsupported>,
 }
 
 impl<'conf> Generator<'conf> {
-    const DIST_PATH: &'static str = "dist";
-
-    fn new(conf: &'conf Conf) -> Result<Self, Error> {
-        create_dir_all(Self::DIST_PATH)?;
+    fn new(conf: &'conf Conf, build: &'conf Build, reload: bool) -> Result<Self, Error> {
+        let out = &build.out;
+        create_dir_all(out)?;
+        if build.prune {
+            check_prune(&build.root, out)?;
+        }
 
         Ok(Self {
             conf,
-            meta: Meta::read()?,
-            deps: HashSet::new(),
-            langs: HashSet::new(),
-            posts: HashMap::new(),
+            out,
+            reload,
+            prune: build.prune,
+            meta: rename(Meta::read(&conf.meta_path())?, &conf.articles),
+            cache: Cache::read(out, build.force),
+            deps: BTreeSet::new(),
+            listed: conf
+                .articles
+                .iter()
+                .flat_map(|(_, info)| info)
+                .filter(|(_, article)| !article.unlisted)
+                .map(|(&lang, _)| lang)
+                .collect(),
+            posts: BTreeMap::new(),
+            unsupported: vec![],
         })
     }
 
@@ -84,238 +167,836 @@ impl<'conf> Generator<'conf> {
         &mut self,
         name: &'conf str,
     ) -> impl FnMut(Langs<'_>, &'conf Article) -> Result<(), Error> {
-        let meta = self
-            .meta
-            .articles
-            .entry(name.to_owned())
-            .or_insert_with(|| ArticleMeta {
-                date: date::now(),
-                langs: BTreeSet::new(),
-            });
-
+        let articles = &mut self.meta.articles;
         let conf = self.conf;
+        let out = self.out;
+        let reload = self.reload;
+        let cache = &mut self.cache;
         let deps = &mut self.deps;
-        let langs = &mut self.langs;
+        let listed = &self.listed;
         let posts = &mut self.posts;
+        let unsupported = &mut self.unsupported;
 
-        move |Langs { lang, more }, Article { title }| {
-            posts.entry(lang).or_default().push(Post {
-                name,
-                title,
-                date: meta.date,
-            });
+        move |Langs { lang, more }, article| {
+            let date = if article.draft {
+                // drafts aren't stamped, so the publish date is set on release
+                articles.get(name).map_or(conf.now, |meta| meta.date)
+            } else {
+                let meta = articles
+                    .entry(name.to_owned())
+                    .or_insert_with(|| ArticleMeta {
+                        date: conf.now,
+                        langs: BTreeSet::new(),
+                    });
+
+                meta.langs.insert(lang);
+                meta.date
+            };
+
+            let date = article.date.unwrap_or(date);
+            let article_path = conf.article_path(name, lang);
+            if let Some(updated) = article.updated
+                && updated.before(date)
+            {
+                eprintln!(
+                    "the article {} is updated on {} before its date {}",
+                    article_path.display(),
+                    updated.iso(),
+                    date.iso(),
+                );
 
-            if langs.insert(lang) {
-                create_dir_all(&format!("{}/{lang}", Self::DIST_PATH))?;
+                return Err(ErrorKind::InvalidData.into());
             }
 
-            let article_path = format!("{lang}/{name}.md");
-            let page_path = format!("{}/{lang}/{name}.html", Self::DIST_PATH);
-            println!("generate {page_path}");
+            let page_path = out.join(format!("{lang}/{name}.html"));
 
-            let md = match read(&article_path) {
-                Read::Content(s) => s,
-                Read::NotFound => {
-                    eprintln!("{article_path} not found!");
-                    return Ok(());
-                }
-                Read::Failed(e) => return Err(e),
-            };
+            if let Some(cover) = &article.cover {
+                deps.insert(cover.clone());
+            }
 
-            let mut translations = more.map(|lang| Translation {
-                lang,
-                href: format!("{lang}/{name}.html"),
-            });
+            let l = conf.local.bind(lang);
 
-            let page = html::make(Make {
-                l: conf.local.bind(lang),
-                blog: &conf.blog.title,
-                title,
-                translations: &mut translations,
-                social: &conf.social,
-                target: Target::Article {
-                    md: &md,
-                    date: meta.date,
-                    index_href: format!("{lang}.html"),
-                    deps,
+            // the rendering depends on localized alert titles
+            let inputs = cache::hash(&(conf.settings, &article.md));
+            let rendered = match cache.rendered(&article_path, inputs) {
+                Some(rendered) => rendered,
+                None => match html::md_to_html(&article.md, &article_path, l) {
+                    Ok(rendered) => {
+                        cache.render(&article_path, inputs, rendered.clone());
+                        rendered
+                    }
+                    Err(errors) => {
+                        // keep generating to report all errors at once
+                        unsupported.extend(errors);
+                        return Ok(());
+                    }
                 },
-            });
+            };
+
+            for warning in &rendered.warnings {
+                warning!("{warning}");
+            }
 
-            write(&page_path, page.into_string().as_bytes())?;
-            meta.langs.insert(lang);
+            deps.extend(rendered.deps.iter().cloned());
+
+            let minutes = rendered.minutes(l.wpm());
+
+            // the excerpt describes an article without a description
+            let description = match &article.description {
+                Some(description) => Some(String::from(&**description)),
+                None if rendered.excerpt.is_empty() => None,
+                None => Some(html::trim_excerpt(&rendered.excerpt, conf.blog.excerpt_len)),
+            };
+
+            let more: Vec<_> = more.collect();
+            let indexed = listed.contains(&lang);
+
+            let inputs = cache::hash(&(conf.settings, reload, article, date, &more, indexed));
+            if cache.fresh(&page_path, inputs) {
+                debug!("skip {}", page_path.display());
+            } else {
+                info!("generate {}", page_path.display());
+                let mut translations = more.iter().map(|&lang| Translation {
+                    lang,
+                    href: format!("{lang}/{name}.html"),
+                });
+
+                let page = html::make(Make {
+                    l,
+                    blog: &conf.blog.title,
+                    title: &article.title,
+                    href: format!("{lang}/{name}.html"),
+                    base_url: conf.blog.base_url.as_deref(),
+                    translations: &mut translations,
+                    social: &conf.social,
+                    // feeds are made of listed articles only
+                    feeds: if indexed {
+                        conf.blog.feeds()
+                    } else {
+                        Feeds::default()
+                    },
+                    excerpts: conf.blog.excerpts,
+                    reload,
+                    target: Target::Article {
+                        content: &rendered.content,
+                        description: description.as_deref(),
+                        tags: &article.tags,
+                        cover: article.cover.as_deref(),
+                        toc: Some(&*rendered.toc).filter(|toc| article.toc && !toc.is_empty()),
+                        image: rendered.image.as_deref(),
+                        date,
+                        updated: article.updated,
+                        minutes,
+                        index_href: indexed.then(|| format!("{lang}.html")),
+                        listed: !article.unlisted,
+                    },
+                });
+
+                create_dir_all(&out.join(lang.to_string()))?;
+                write(&page_path, page.into_string().as_bytes())?;
+            }
+
+            if article.unlisted {
+                debug!("unlisted {}", page_path.display());
+                return Ok(());
+            }
+
+            posts.entry(lang).or_default().push(Post {
+                name,
+                title: &article.title,
+                date,
+                updated: article.updated,
+                minutes,
+                tags: &article.tags,
+                description,
+                content: rendered.content,
+                text: rendered.text,
+            });
 
             Ok(())
         }
     }
 
+    /// Reports all errors found in articles.
+    fn check(&self) -> Result<(), Error> {
+        if self.unsupported.is_empty() {
+            return Ok(());
+        }
+
+        for e in &self.unsupported {
+            eprintln!("error: {e}");
+        }
+
+        let n = self.unsupported.len();
+        Err(Error::other(format!(
+            "found {n} unsupported markdown construct(s)"
+        )))
+    }
+
     fn generate_list(&mut self) -> Result<(), Error> {
         for (&lang, posts) in &mut self.posts {
-            let page_path = format!("{}/{lang}.html", Self::DIST_PATH);
-            println!("generate {page_path}");
+            let page_path = self.out.join(format!("{lang}.html"));
 
-            posts.sort_by_key(|p| Reverse(p.by_date()));
+            posts.sort_by_key(|p| Reverse(p.date));
 
-            let mut translations = self
-                .langs
-                .iter()
-                .copied()
-                .filter(|l| *l != lang)
-                .map(|lang| Translation {
+            let more: Vec<_> = self.listed.iter().copied().filter(|l| *l != lang).collect();
+
+            let inputs = cache::hash(&(self.conf.settings, self.reload, lang, &more, &posts));
+            if self.cache.fresh(&page_path, inputs) {
+                debug!("skip {}", page_path.display());
+            } else {
+                info!("generate {}", page_path.display());
+                let mut translations = more.iter().map(|&lang| Translation {
                     lang,
                     href: format!("{lang}.html"),
                 });
 
-            let page = html::make(Make {
-                l: self.conf.local.bind(lang),
+                let page = html::make(Make {
+                    l: self.conf.local.bind(lang),
+                    blog: &self.conf.blog.title,
+                    title: "",
+                    href: format!("{lang}.html"),
+                    base_url: self.conf.blog.base_url.as_deref(),
+                    translations: &mut translations,
+                    social: &self.conf.social,
+                    feeds: self.conf.blog.feeds(),
+                    excerpts: self.conf.blog.excerpts,
+                    reload: self.reload,
+                    target: Target::List(posts),
+                });
+
+                write(&page_path, page.into_string().as_bytes())?;
+            }
+
+            let index_path = self.out.join(format!("{lang}.search.json"));
+            if self.cache.fresh(&index_path, cache::hash(&(lang, &posts))) {
+                debug!("skip {}", index_path.display());
+            } else {
+                info!("generate {}", index_path.display());
+                write(&index_path, search::index(lang, posts).as_bytes())?;
+            }
+
+            let Some(base_url) = &self.conf.blog.base_url else {
+                continue;
+            };
+
+            let feed = Feed {
+                base_url: base_url.trim_end_matches('/'),
                 blog: &self.conf.blog.title,
-                title: "",
-                translations: &mut translations,
-                social: &self.conf.social,
-                target: Target::List(posts),
-            });
+                lang,
+                posts,
+            };
+
+            let inputs = cache::hash(&(self.conf.settings, lang, &posts));
+            let feed_path = self.out.join(format!("{lang}.atom"));
+            if self.cache.fresh(&feed_path, inputs) {
+                debug!("skip {}", feed_path.display());
+            } else {
+                info!("generate {}", feed_path.display());
+                write(&feed_path, feed::atom(&feed).as_bytes())?;
+            }
+
+            if self.conf.blog.rss {
+                let feed_path = self.out.join(format!("{lang}.rss"));
+                if self.cache.fresh(&feed_path, inputs) {
+                    debug!("skip {}", feed_path.display());
+                } else {
+                    info!("generate {}", feed_path.display());
+                    write(&feed_path, feed::rss(&feed).as_bytes())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Generates the sitemap and the `robots.txt` pointing at it.
+    fn generate_sitemap(&mut self) -> Result<(), Error> {
+        let Some(base_url) = &self.conf.blog.base_url else {
+            return Ok(());
+        };
+
+        let base_url = base_url.trim_end_matches('/');
+        let sitemap_path = self.out.join("sitemap.xml");
+        if self
+            .cache
+            .fresh(&sitemap_path, cache::hash(&(base_url, &self.posts)))
+        {
+            debug!("skip {}", sitemap_path.display());
+        } else {
+            info!("generate {}", sitemap_path.display());
+            let sitemap = sitemap::sitemap(base_url, &self.posts);
+            write(&sitemap_path, sitemap.as_bytes())?;
+        }
+
+        let rules = self.conf.blog.robots.as_deref();
+        let robots_path = self.out.join("robots.txt");
+        if self
+            .cache
+            .fresh(&robots_path, cache::hash(&(base_url, rules)))
+        {
+            debug!("skip {}", robots_path.display());
+        } else {
+            info!("generate {}", robots_path.display());
+            write(&robots_path, sitemap::robots(base_url, rules).as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    fn generate_tags(&mut self) -> Result<(), Error> {
+        let tags: BTreeMap<_, _> = self
+            .posts
+            .iter()
+            .map(|(&lang, posts)| (lang, group_tags(posts)))
+            .filter(|(_, tags)| !tags.is_empty())
+            .collect();
+
+        for (&lang, lang_tags) in &tags {
+            create_dir_all(&self.out.join(format!("{lang}/tags")))?;
+
+            let more: Vec<_> = tags.keys().copied().filter(|l| *l != lang).collect();
+
+            let page_path = self.out.join(format!("{lang}/tags/index.html"));
+            let inputs = cache::hash(&(self.conf.settings, self.reload, lang, &more, lang_tags));
+            if self.cache.fresh(&page_path, inputs) {
+                debug!("skip {}", page_path.display());
+            } else {
+                info!("generate {}", page_path.display());
+                let mut translations = more.iter().map(|&lang| Translation {
+                    lang,
+                    href: format!("{lang}/tags/index.html"),
+                });
+
+                let page = html::make(Make {
+                    l: self.conf.local.bind(lang),
+                    blog: &self.conf.blog.title,
+                    title: "",
+                    href: format!("{lang}/tags/index.html"),
+                    base_url: self.conf.blog.base_url.as_deref(),
+                    translations: &mut translations,
+                    social: &self.conf.social,
+                    feeds: self.conf.blog.feeds(),
+                    excerpts: self.conf.blog.excerpts,
+                    reload: self.reload,
+                    target: Target::Tags(lang_tags),
+                });
+
+                write(&page_path, page.into_string().as_bytes())?;
+            }
+
+            for tag_posts in lang_tags {
+                let slug = html::slug(tag_posts.tag);
+
+                // translations are the same tag in other languages
+                let more: Vec<_> = tags
+                    .iter()
+                    .filter(|&(&l, tags)| {
+                        l != lang && tags.iter().any(|t| html::slug(t.tag) == slug)
+                    })
+                    .map(|(&l, _)| l)
+                    .collect();
+
+                let page_path = self.out.join(html::tag_href(lang, tag_posts.tag));
+                let inputs =
+                    cache::hash(&(self.conf.settings, self.reload, lang, &more, tag_posts));
+
+                if self.cache.fresh(&page_path, inputs) {
+                    debug!("skip {}", page_path.display());
+                    continue;
+                }
+
+                info!("generate {}", page_path.display());
+                let mut translations = more.iter().map(|&lang| Translation {
+                    lang,
+                    href: html::tag_href(lang, tag_posts.tag),
+                });
+
+                let page = html::make(Make {
+                    l: self.conf.local.bind(lang),
+                    blog: &self.conf.blog.title,
+                    title: "",
+                    href: html::tag_href(lang, tag_posts.tag),
+                    base_url: self.conf.blog.base_url.as_deref(),
+                    translations: &mut translations,
+                    social: &self.conf.social,
+                    feeds: self.conf.blog.feeds(),
+                    excerpts: self.conf.blog.excerpts,
+                    reload: self.reload,
+                    target: Target::Tag(tag_posts),
+                });
+
+                write(&page_path, page.into_string().as_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Generates pages redirecting old names of articles to the current ones.
+    fn generate_aliases(&mut self) -> Result<(), Error> {
+        let base_url = self.conf.blog.base_url.as_deref();
+        for (name, info) in &self.conf.articles {
+            let aliases = aliases(info);
+            for lang in info.keys() {
+                for alias in &aliases {
+                    let page_path = self.out.join(format!("{lang}/{alias}.html"));
+                    let inputs = cache::hash(&(lang, name, base_url));
+                    if self.cache.fresh(&page_path, inputs) {
+                        debug!("skip {}", page_path.display());
+                        continue;
+                    }
+
+                    info!("generate {}", page_path.display());
+                    let page = html::redirect(&format!("{lang}/{name}.html"), 1, base_url);
+                    write(&page_path, page.into_string().as_bytes())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reports meta entries without articles,
+    /// optionally redirects their pages to the list.
+    fn generate_orphans(&mut self) -> Result<(), Error> {
+        let names: BTreeSet<_> = self
+            .conf
+            .articles
+            .iter()
+            .flat_map(|(name, info)| iter::once(&**name).chain(aliases(info)))
+            .collect();
+
+        for (name, meta) in &self.meta.articles {
+            if names.contains(name.as_str()) {
+                continue;
+            }
+
+            if self.prune {
+                warning!("the article {name} from the Meta.toml is not found");
+            } else {
+                debug!("the article {name} from the Meta.toml is not found");
+            }
+
+            if !self.conf.blog.redirect_orphans {
+                continue;
+            }
 
-            write(&page_path, page.into_string().as_bytes())?;
+            // redirect only to existing lists
+            for lang in meta
+                .langs
+                .iter()
+                .filter(|lang| self.posts.contains_key(lang))
+            {
+                let page_path = self.out.join(format!("{lang}/{name}.html"));
+                let base_url = self.conf.blog.base_url.as_deref();
+                if self
+                    .cache
+                    .fresh(&page_path, cache::hash(&(lang, name, base_url)))
+                {
+                    debug!("skip {}", page_path.display());
+                    continue;
+                }
+
+                info!("generate {}", page_path.display());
+                let page = html::redirect(&format!("{lang}.html"), 1, base_url);
+                write(&page_path, page.into_string().as_bytes())?;
+            }
         }
 
         Ok(())
     }
 
-    fn save(self) -> Result<(), Error> {
+    fn save(mut self) -> Result<(), Error> {
         for dep in self.deps {
-            let to = format!("{}/{dep}", Self::DIST_PATH);
-            println!("save {to}");
-            copy(&dep, &to)?;
+            let from = self.conf.root.join(&*dep);
+            let to = self.out.join(&*dep);
+            let contents = fs::read(&from)
+                .inspect_err(|_| eprintln!("failed to read file {}", from.display()))?;
+
+            if self.cache.fresh(&to, cache::hash(&contents)) {
+                debug!("skip {}", to.display());
+            } else {
+                info!("save {}", to.display());
+                write(&to, &contents)?;
+            }
         }
 
         let assets = [
-            ("dist/style.css", &include_bytes!("../assets/style.css")[..]),
-            (
-                "dist/favicon.svg",
-                &include_bytes!("../assets/favicon.svg")[..],
-            ),
+            ("style.css", &include_bytes!("../assets/style.css")[..]),
+            ("favicon.svg", &include_bytes!("../assets/favicon.svg")[..]),
+            ("search.js", &include_bytes!("../assets/search.js")[..]),
             (
-                "dist/alfaslab-n-la.woff2",
+                "alfaslab-n-la.woff2",
                 &include_bytes!("../assets/alfaslab-n-la.woff2")[..],
             ),
             (
-                "dist/carlito-i-cy-700.woff2",
+                "carlito-i-cy-700.woff2",
                 &include_bytes!("../assets/carlito-i-cy-700.woff2")[..],
             ),
             (
-                "dist/carlito-i-cy.woff2",
+                "carlito-i-cy.woff2",
                 &include_bytes!("../assets/carlito-i-cy.woff2")[..],
             ),
             (
-                "dist/carlito-i-la-700.woff2",
+                "carlito-i-la-700.woff2",
                 &include_bytes!("../assets/carlito-i-la-700.woff2")[..],
             ),
             (
-                "dist/carlito-i-la.woff2",
+                "carlito-i-la.woff2",
                 &include_bytes!("../assets/carlito-i-la.woff2")[..],
             ),
             (
-                "dist/carlito-n-cy-700.woff2",
+                "carlito-n-cy-700.woff2",
                 &include_bytes!("../assets/carlito-n-cy-700.woff2")[..],
             ),
             (
-                "dist/carlito-n-cy.woff2",
+                "carlito-n-cy.woff2",
                 &include_bytes!("../assets/carlito-n-cy.woff2")[..],
             ),
             (
-                "dist/carlito-n-la-700.woff2",
+                "carlito-n-la-700.woff2",
                 &include_bytes!("../assets/carlito-n-la-700.woff2")[..],
             ),
             (
-                "dist/carlito-n-la.woff2",
+                "carlito-n-la.woff2",
                 &include_bytes!("../assets/carlito-n-la.woff2")[..],
             ),
             (
-                "dist/jetbrains-m-cy.woff2",
+                "jetbrains-m-cy.woff2",
                 &include_bytes!("../assets/jetbrains-m-cy.woff2")[..],
             ),
             (
-                "dist/jetbrains-m-la.woff2",
+                "jetbrains-m-la.woff2",
                 &include_bytes!("../assets/jetbrains-m-la.woff2")[..],
             ),
         ];
 
-        for (path, contents) in assets {
-            let name = Path::new(path).file_name().unwrap_or_default();
-            println!("save {}", name.display());
-            write(path, contents)?;
+        for (name, contents) in assets {
+            let path = self.out.join(name);
+            if self.cache.fresh(&path, cache::hash(contents)) {
+                debug!("skip {name}");
+            } else {
+                info!("save {name}");
+                write(&path, contents)?;
+            }
+        }
+
+        if self.prune {
+            prune(self.out, &self.cache)?;
         }
 
-        self.meta.write()?;
+        self.meta.write(&self.conf.meta_path())?;
+        self.cache.write();
         Ok(())
     }
 }
 
-#[derive(Deserialize)]
+/// Checks the output directory doesn't contain sources.
+fn check_prune(root: &Path, out: &Path) -> Result<(), Error> {
+    let canonicalize = |path: &Path| {
+        // the empty path is the current directory
+        let path = if path.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            path
+        };
+
+        path.canonicalize()
+            .inspect_err(|_| eprintln!("failed to resolve path {}", path.display()))
+    };
+
+    if canonicalize(root)?.starts_with(canonicalize(out)?) {
+        eprintln!(
+            "the output directory {} contains the blog sources, refuse to prune it",
+            out.display(),
+        );
+
+        return Err(ErrorKind::InvalidInput.into());
+    }
+
+    Ok(())
+}
+
+/// Removes files of the output directory the build didn't produce.
+fn prune(dir: &Path, cache: &Cache) -> Result<(), Error> {
+    let entries = fs::read_dir(dir)
+        .inspect_err(|_| eprintln!("failed to read directory {}", dir.display()))?;
+
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            prune(&path, cache)?;
+
+            let empty = fs::read_dir(&path)?.next().is_none();
+            if empty {
+                info!("remove {}", path.display());
+                fs::remove_dir(&path)
+                    .inspect_err(|_| eprintln!("failed to remove {}", path.display()))?;
+            }
+        } else if !cache.produced(&path) {
+            info!("remove {}", path.display());
+            fs::remove_file(&path)
+                .inspect_err(|_| eprintln!("failed to remove {}", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns old names of the article declared in any language.
+fn aliases(info: &ArticleInfo) -> BTreeSet<&str> {
+    info.values()
+        .flat_map(|article| &article.aliases)
+        .map(|alias| &**alias)
+        .collect()
+}
+
+/// Moves meta entries of renamed articles to their current names,
+/// so the original date is kept.
+fn rename(mut meta: Meta, articles: &[(Box<str>, ArticleInfo)]) -> Meta {
+    for (name, info) in articles {
+        if meta.articles.contains_key(&**name) {
+            continue;
+        }
+
+        for alias in aliases(info) {
+            if let Some(entry) = meta.articles.remove(alias) {
+                info!("rename the article {alias} to {name} in the Meta.toml");
+                meta.articles.insert(name.to_string(), entry);
+                break;
+            }
+        }
+    }
+
+    meta
+}
+
+/// Groups posts by tags sorted by slug.
+fn group_tags<'post>(posts: &'post [Post<'post>]) -> Vec<TagPosts<'post>> {
+    let mut tags = BTreeMap::new();
+    for post in posts {
+        for tag in post.tags {
+            tags.entry(html::slug(tag))
+                .or_insert_with(|| TagPosts { tag, posts: vec![] })
+                .posts
+                .push(post);
+        }
+    }
+
+    tags.into_values().collect()
+}
+
+#[derive(Deserialize, Hash)]
 struct Blog {
     title: Box<str>,
+    /// The absolute url of the deployed blog, required to generate feeds and the sitemap.
+    base_url: Option<Box<str>>,
+    /// Generate an RSS feed in addition to the Atom one.
+    #[serde(default)]
+    rss: bool,
+    /// Redirect pages of removed articles to the list.
+    #[serde(default)]
+    redirect_orphans: bool,
+    /// Show article descriptions in lists.
+    #[serde(default)]
+    excerpts: bool,
+    /// The maximum length of excerpts in chars.
+    #[serde(default = "Blog::excerpt_len")]
+    excerpt_len: usize,
+    /// Rules of the `robots.txt`, everything is allowed by default.
+    robots: Option<Box<str>>,
+}
+
+impl Blog {
+    fn excerpt_len() -> usize {
+        160
+    }
+
+    fn feeds(&self) -> Feeds {
+        let atom = self.base_url.is_some();
+        Feeds {
+            atom,
+            rss: atom && self.rss,
+        }
+    }
 }
 
 impl Default for Blog {
     fn default() -> Self {
         Self {
             title: Box::from("Blog title"),
+            base_url: None,
+            rss: false,
+            redirect_orphans: false,
+            excerpts: false,
+            excerpt_len: Self::excerpt_len(),
+            robots: None,
         }
     }
 }
 
+/// The article settings from the `Milky.toml`
+/// or from the front matter of the article file.
 #[derive(Deserialize)]
+struct ArticleSettings {
+    title: Option<Box<str>>,
+    description: Option<Box<str>>,
+    date: Option<Date>,
+    updated: Option<Date>,
+    tags: Option<Vec<Box<str>>>,
+    aliases: Option<Vec<Box<str>>>,
+    draft: Option<bool>,
+    unlisted: Option<bool>,
+    cover: Option<Box<str>>,
+    toc: Option<bool>,
+}
+
+impl ArticleSettings {
+    /// Merges the settings with the front matter ones,
+    /// the front matter takes precedence.
+    fn merge(self, front: Self) -> Self {
+        Self {
+            title: front.title.or(self.title),
+            description: front.description.or(self.description),
+            date: front.date.or(self.date),
+            updated: front.updated.or(self.updated),
+            tags: front.tags.or(self.tags),
+            aliases: front.aliases.or(self.aliases),
+            draft: front.draft.or(self.draft),
+            unlisted: front.unlisted.or(self.unlisted),
+            cover: front.cover.or(self.cover),
+            toc: front.toc.or(self.toc),
+        }
+    }
+}
+
+/// The article with its resolved settings.
+#[derive(Hash)]
 struct Article {
     title: Box<str>,
+    description: Option<Box<str>>,
+    date: Option<Date>,
+    updated: Option<Date>,
+    tags: Vec<Box<str>>,
+    /// Old names of the article redirected to the current one.
+    aliases: Vec<Box<str>>,
+    draft: bool,
+    /// The page is generated but not listed anywhere.
+    unlisted: bool,
+    cover: Option<Box<str>>,
+    /// Show the table of contents at the top of the article.
+    toc: bool,
+    md: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Hash)]
 struct Social {
     href: Box<str>,
     icon: Icon,
 }
 
-type ArticleInfo = HashMap<Lang, Article>;
+type ArticleInfo = BTreeMap<Lang, Article>;
 
 struct Conf {
+    root: PathBuf,
+    /// The hash of settings shared by all pages.
+    settings: u64,
+    /// The current date, articles dated after it are deferred.
+    now: Date,
     blog: Blog,
     articles: Vec<(Box<str>, ArticleInfo)>,
     social: Vec<Social>,
     local: Local,
 }
 
-fn read_conf() -> Result<Conf, Error> {
+impl Conf {
+    fn article_path(&self, name: &str, lang: Lang) -> PathBuf {
+        article_path(&self.root, name, lang)
+    }
+
+    fn meta_path(&self) -> PathBuf {
+        self.root.join("Meta.toml")
+    }
+}
+
+fn read_conf(build: &Build, sources: &mut Vec<PathBuf>) -> Result<Conf, Error> {
     #[derive(Deserialize)]
     struct Scheme {
         #[serde(default)]
         blog: Blog,
         #[serde(default)]
-        article: HashMap<Box<str>, ArticleInfo>,
+        article: BTreeMap<Box<str>, BTreeMap<Lang, ArticleSettings>>,
         #[serde(default)]
         social: Vec<Social>,
     }
 
-    let conf_path = "Milky.toml";
+    sources.push(build.config.clone());
+    let conf_path = &build.config;
+    debug!("read {}", conf_path.display());
     let conf = read(conf_path).into_result()?;
     let scheme: Scheme = toml::from_str(&conf)
-        .inspect_err(|_| eprintln!("failed to deserialize file {conf_path}"))
+        .inspect_err(|_| eprintln!("failed to deserialize file {}", conf_path.display()))
         .map_err(Error::other)?;
 
-    let mut articles: Vec<_> = scheme.article.into_iter().collect();
-    articles.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let now = build.now.unwrap_or_else(date::now);
+    let mut articles = vec![];
+    for (name, info) in scheme.article {
+        let mut loaded = ArticleInfo::new();
+        for (lang, settings) in info {
+            let article_path = article_path(&build.root, &name, lang);
+            sources.push(article_path.clone());
+            let Some(article) = read_article(&article_path, settings)? else {
+                continue;
+            };
 
-    let local_path = "Local.toml";
-    let local = match read(local_path) {
-        Read::Content(s) => toml::from_str(&s)
-            .inspect_err(|_| eprintln!("failed to deserialize file {local_path}"))
-            .map_err(Error::other)?,
+            if article.draft && !build.drafts {
+                info!("skip draft {}", article_path.display());
+                continue;
+            }
+
+            if let Some(date) = article.date
+                && date.after(now)
+            {
+                info!("defer {} until {}", article_path.display(), date.iso());
+                continue;
+            }
+
+            loaded.insert(lang, article);
+        }
+
+        if !loaded.is_empty() {
+            articles.push((name, loaded));
+        }
+    }
+
+    check_aliases(&articles)?;
+    check_tags(&articles)?;
+
+    let local_path = build.root.join("Local.toml");
+    sources.push(local_path.clone());
+    let local_text = match read(&local_path) {
+        Read::Content(s) => Some(s),
         Read::NotFound => {
-            eprintln!("file Local.toml not found");
-            Local::new()
+            warning!("file {} not found", local_path.display());
+            None
         }
         Read::Failed(e) => return Err(e),
     };
 
+    let local = match &local_text {
+        Some(s) => toml::from_str(s)
+            .inspect_err(|_| eprintln!("failed to deserialize file {}", local_path.display()))
+            .map_err(Error::other)?,
+        None => Local::new(),
+    };
+
+    let settings = cache::hash(&(&scheme.blog, &scheme.social, &local_text));
+
     Ok(Conf {
+        root: build.root.clone(),
+        settings,
+        now,
         blog: scheme.blog,
         articles,
         social: scheme.social,
@@ -323,55 +1004,118 @@ fn read_conf() -> Result<Conf, Error> {
     })
 }
 
-#[derive(Serialize, Deserialize)]
-struct ArticleMeta {
-    date: Date,
-    #[serde(default)]
-    langs: BTreeSet<Lang>,
-}
+/// Checks aliases don't conflict with each other or article names.
+fn check_aliases(articles: &[(Box<str>, ArticleInfo)]) -> Result<(), Error> {
+    let mut names: BTreeSet<_> = articles.iter().map(|(name, _)| &**name).collect();
+    for (name, info) in articles {
+        for alias in aliases(info) {
+            if alias.is_empty() || alias.contains(['/', '\\']) {
+                eprintln!("the article {name} has an invalid alias {alias:?}");
+                return Err(ErrorKind::InvalidData.into());
+            }
 
-#[derive(Serialize, Deserialize)]
-struct Meta {
-    version: u32,
-    articles: BTreeMap<String, ArticleMeta>,
+            if !names.insert(alias) {
+                eprintln!("the alias {alias} of the article {name} is already used");
+                return Err(ErrorKind::InvalidData.into());
+            }
+        }
+    }
+
+    Ok(())
 }
 
-impl Meta {
-    const VERSION: u32 = 0;
+/// Checks tags of each language get distinct pages.
+fn check_tags(articles: &[(Box<str>, ArticleInfo)]) -> Result<(), Error> {
+    let mut slugs = BTreeMap::new();
+    for (name, info) in articles {
+        for (&lang, article) in info {
+            for tag in &article.tags {
+                let slug = html::slug(tag);
+                let first = slugs.entry((lang, slug)).or_insert(&**tag);
+                if *first != &**tag {
+                    eprintln!(
+                        "the tag {tag:?} of the article {name} has the same page as the tag {first:?}",
+                    );
 
-    fn new() -> Self {
-        Self {
-            version: Self::VERSION,
-            articles: BTreeMap::new(),
+                    return Err(ErrorKind::InvalidData.into());
+                }
+            }
         }
     }
 
-    fn read() -> Result<Self, Error> {
-        let meta_path = "Meta.toml";
-        let meta = match read(meta_path) {
-            Read::Content(s) => s,
-            Read::NotFound => {
-                eprintln!("create the Meta.toml");
-                return Ok(Self::new());
-            }
-            Read::Failed(e) => return Err(e),
-        };
+    Ok(())
+}
+
+fn article_path(root: &Path, name: &str, lang: Lang) -> PathBuf {
+    root.join(format!("{lang}/{name}.md"))
+}
+
+fn read_article(article_path: &Path, settings: ArticleSettings) -> Result<Option<Article>, Error> {
+    debug!("read {}", article_path.display());
+    let md = match read(article_path) {
+        Read::Content(s) => s,
+        Read::NotFound => {
+            warning!("{} not found!", article_path.display());
+            return Ok(None);
+        }
+        Read::Failed(e) => return Err(e),
+    };
+
+    let settings = match html::front_matter(&md) {
+        Some(front) => {
+            let front = toml::from_str(&front)
+                .inspect_err(|_| {
+                    eprintln!(
+                        "failed to deserialize front matter of {}",
+                        article_path.display(),
+                    );
+                })
+                .map_err(Error::other)?;
+
+            settings.merge(front)
+        }
+        None => settings,
+    };
 
-        let meta = toml::from_str(&meta)
-            .inspect_err(|_| eprintln!("failed to deserialize file {meta_path}"))
-            .map_err(Error::other)?;
+    let Some(title) = settings.title.filter(|title| !title.is_empty()) else {
+        eprintln!("the article {} has no title", article_path.display());
+        return Err(ErrorKind::InvalidData.into());
+    };
+
+    let article = Article {
+        title,
+        description: settings.description,
+        date: settings.date,
+        updated: settings.updated,
+        tags: settings.tags.unwrap_or_default(),
+        aliases: settings.aliases.unwrap_or_default(),
+        draft: settings.draft.unwrap_or_default(),
+        unlisted: settings.unlisted.unwrap_or_default(),
+        cover: settings.cover,
+        toc: settings.toc.unwrap_or_default(),
+        md,
+    };
+
+    if let Some(tag) = article.tags.iter().find(|tag| html::slug(tag).is_empty()) {
+        eprintln!(
+            "the article {} has the tag {tag:?} without letters or digits",
+            article_path.display(),
+        );
 
-        Ok(meta)
+        return Err(ErrorKind::InvalidData.into());
     }
 
-    fn write(self) -> Result<(), Error> {
-        let meta = toml::to_string(&self)
-            .inspect_err(|_| eprintln!("failed to serialize meta info"))
-            .map_err(Error::other)?;
+    // the slug is taken by the page of all tags
+    if let Some(tag) = article.tags.iter().find(|tag| html::slug(tag) == "index") {
+        eprintln!(
+            "the article {} has the reserved tag {tag:?}",
+            article_path.display(),
+        );
 
-        write("Meta.toml", meta.as_bytes())?;
-        Ok(())
+        return Err(ErrorKind::InvalidData.into());
     }
+
+    Ok(Some(article))
 }
 
 enum Read {
@@ -390,26 +1134,82 @@ impl Read {
     }
 }
 
-fn read(path: &str) -> Read {
+fn read(path: &Path) -> Read {
     match fs::read_to_string(path) {
         Ok(s) => Read::Content(s),
         Err(e) if e.kind() == ErrorKind::NotFound => Read::NotFound,
         Err(e) => {
-            eprintln!("failed to read file {path}");
+            eprintln!("failed to read file {}", path.display());
             Read::Failed(e)
         }
     }
 }
 
-fn write(path: &str, contents: &[u8]) -> Result<(), Error> {
-    fs::write(path, contents).inspect_err(|_| eprintln!("failed to write file {path}"))
+fn write(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    fs::write(path, contents).inspect_err(|_| eprintln!("failed to write file {}", path.display()))
 }
 
-fn create_dir_all(path: &str) -> Result<(), Error> {
-    fs::create_dir_all(path).inspect_err(|_| eprintln!("failed to create {path} directory"))
+fn create_dir_all(path: &Path) -> Result<(), Error> {
+    fs::create_dir_all(path)
+        .inspect_err(|_| eprintln!("failed to create {} directory", path.display()))
 }
 
-fn copy(from: &str, to: &str) -> Result<(), Error> {
-    fs::copy(from, to).inspect_err(|_| eprintln!("failed to copy from {from} to {to}"))?;
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn front_matter_overrides() {
+        let settings = |s| toml::from_str::<ArticleSettings>(s).expect("valid settings");
+        let merged = settings("draft = true\ntags = [\"a\"]\ntoc = true")
+            .merge(settings("draft = false\ntags = []"));
+
+        assert_eq!(merged.draft, Some(false));
+        assert_eq!(merged.tags, Some(vec![]));
+        assert_eq!(merged.toc, Some(true));
+    }
+
+    #[test]
+    fn colliding_tags() {
+        let article = |tags: &[&str]| Article {
+            title: Box::from("title"),
+            description: None,
+            date: None,
+            updated: None,
+            tags: tags.iter().map(|&tag| Box::from(tag)).collect(),
+            aliases: vec![],
+            draft: false,
+            unlisted: false,
+            cover: None,
+            toc: false,
+            md: String::new(),
+        };
+
+        let lang = |s| Lang::from_ascii(s).expect("valid lang");
+        let articles = |en, ru| {
+            vec![
+                (
+                    Box::from("a"),
+                    ArticleInfo::from([(lang(*b"en"), article(en))]),
+                ),
+                (
+                    Box::from("b"),
+                    ArticleInfo::from([(lang(*b"ru"), article(ru))]),
+                ),
+            ]
+        };
+
+        assert!(
+            check_tags(&articles(&["Rust"], &["rust"])).is_ok(),
+            "other languages"
+        );
+        assert!(
+            check_tags(&articles(&["Rust", "Rust"], &[])).is_ok(),
+            "same tag"
+        );
+        assert!(
+            check_tags(&articles(&["Rust", "rust"], &[])).is_err(),
+            "same slug"
+        );
+    }
 }
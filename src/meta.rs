@@ -0,0 +1,192 @@
+use {
+    crate::{Read, date::Date, lang::Lang, log::info, read, write},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        fmt,
+        io::Error,
+        iter,
+        path::Path,
+    },
+    toml::{
+        Table, Value,
+        value::{self, Datetime},
+    },
+};
+
+/// The meta info of published articles.
+///
+/// It's committed to the blog repository, so the format is versioned
+/// and files of older versions are migrated on read.
+#[derive(Serialize, Deserialize)]
+pub struct Meta {
+    version: u32,
+    #[serde(default)]
+    pub articles: BTreeMap<String, ArticleMeta>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArticleMeta {
+    pub date: Date,
+    #[serde(default)]
+    pub langs: BTreeSet<Lang>,
+}
+
+type Migration = fn(&mut Table) -> Result<(), String>;
+
+/// Migrations of the meta info, the n-th one upgrades the version n to n + 1.
+const MIGRATIONS: [Migration; Meta::VERSION as usize] = [v0_dates];
+
+impl Meta {
+    pub const VERSION: u32 = 1;
+
+    fn new() -> Self {
+        Self {
+            version: Self::VERSION,
+            articles: BTreeMap::new(),
+        }
+    }
+
+    pub fn read(meta_path: &Path) -> Result<Self, Error> {
+        let meta = match read(meta_path) {
+            Read::Content(s) => s,
+            Read::NotFound => {
+                info!("create the {}", meta_path.display());
+                return Ok(Self::new());
+            }
+            Read::Failed(e) => return Err(e),
+        };
+
+        Self::parse(&meta)
+            .inspect_err(|_| eprintln!("failed to read file {}", meta_path.display()))
+            .map_err(|e| Error::other(e.to_string()))
+    }
+
+    fn parse(s: &str) -> Result<Self, MetaError> {
+        let mut meta: Table = toml::from_str(s).map_err(MetaError::Deserialize)?;
+        let version = meta
+            .get("version")
+            .and_then(Value::as_integer)
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or(MetaError::Version)?;
+
+        if version > Self::VERSION {
+            return Err(MetaError::Newer(version));
+        }
+
+        for (from, migrate) in iter::zip(version.., &MIGRATIONS[version as usize..]) {
+            info!("migrate the meta info from version {from}");
+            migrate(&mut meta).map_err(|reason| MetaError::Migrate { from, reason })?;
+        }
+
+        meta.insert(String::from("version"), Value::from(Self::VERSION));
+
+        // deserialize from the text since toml values lose datetimes
+        toml::from_str(&meta.to_string()).map_err(MetaError::Deserialize)
+    }
+
+    pub fn write(self, meta_path: &Path) -> Result<(), Error> {
+        let meta = toml::to_string(&self)
+            .inspect_err(|_| eprintln!("failed to serialize meta info"))
+            .map_err(Error::other)?;
+
+        write(meta_path, meta.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Replaces date tables of numbers with TOML dates.
+fn v0_dates(meta: &mut Table) -> Result<(), String> {
+    let Some(Value::Table(articles)) = meta.get_mut("articles") else {
+        return Ok(());
+    };
+
+    for (name, article) in articles {
+        let Some(date) = article.get_mut("date") else {
+            continue;
+        };
+
+        let field = |key| date.get(key).and_then(Value::as_integer);
+        let (Some(day), Some(month), Some(year)) = (field("day"), field("month"), field("year"))
+        else {
+            return Err(format!("the article {name} has no date"));
+        };
+
+        let (Ok(day), Ok(month), Ok(year)) =
+            (u8::try_from(day), u8::try_from(month), u16::try_from(year))
+        else {
+            return Err(format!("the article {name} has an invalid date"));
+        };
+
+        *date = Value::Datetime(Datetime {
+            date: Some(value::Date { year, month, day }),
+            time: None,
+            offset: None,
+        });
+    }
+
+    Ok(())
+}
+
+enum MetaError {
+    Deserialize(toml::de::Error),
+    Version,
+    Newer(u32),
+    Migrate { from: u32, reason: String },
+}
+
+impl fmt::Display for MetaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Deserialize(e) => write!(f, "{e}"),
+            Self::Version => f.write_str("missing or invalid version of meta info"),
+            Self::Newer(version) => write!(
+                f,
+                "meta info of version {version} is newer than supported version {}, \
+                please update milky",
+                Meta::VERSION,
+            ),
+            Self::Migrate { from, reason } => {
+                write!(
+                    f,
+                    "failed to migrate meta info from version {from}: {reason}"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_v0() {
+        let meta = "\
+            version = 0\n\
+            [articles.hello]\n\
+            langs = [\"en\"]\n\
+            [articles.hello.date]\n\
+            day = 3\n\
+            month = 2\n\
+            year = 2024\n";
+
+        let meta = Meta::parse(meta).ok().expect("migrated meta");
+        assert_eq!(meta.version, Meta::VERSION);
+        assert_eq!(meta.articles["hello"].date.iso(), "2024-02-03");
+    }
+
+    #[test]
+    fn newer() {
+        let meta = format!("version = {}", Meta::VERSION + 1);
+        assert!(
+            matches!(Meta::parse(&meta), Err(MetaError::Newer(_))),
+            "newer version",
+        );
+
+        assert!(
+            matches!(Meta::parse("[articles]"), Err(MetaError::Version)),
+            "no version",
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use {
+    crate::{html::Post, lang::Lang},
+    std::{collections::BTreeSet, fmt::Write},
+};
+
+/// Creates a json search index of posts for the client-side search.
+///
+/// Each entry has the post title, slug, date and
+/// the space separated list of unique words of the post text.
+pub fn index(lang: Lang, posts: &[Post<'_>]) -> String {
+    let mut json = String::from("[");
+    for (n, post) in posts.iter().enumerate() {
+        if n > 0 {
+            json.push(',');
+        }
+
+        json.push_str("{\"title\":");
+        string(post.title, &mut json);
+        json.push_str(",\"slug\":");
+        string(post.name, &mut json);
+        json.push_str(",\"url\":");
+        string(&format!("{lang}/{}.html", post.name), &mut json);
+        json.push_str(",\"date\":");
+        string(&post.date.iso(), &mut json);
+        json.push_str(",\"words\":");
+        string(&tokenize(&post.text).join(" "), &mut json);
+        json.push('}');
+    }
+
+    json.push(']');
+    json
+}
+
+/// Splits the text into unique lowercase words.
+fn tokenize(text: &str) -> Vec<String> {
+    let words: BTreeSet<_> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    words.into_iter().collect()
+}
+
+/// Writes a json string literal.
+///
+/// The `<` is escaped too, so the literal is safe to embed in a script.
+pub fn string(s: &str, json: &mut String) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            '<' => json.push_str("\\u003c"),
+            c if c.is_control() => _ = write!(json, "\\u{:04x}", c as u32),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_text() {
+        assert_eq!(
+            tokenize("Hello, world! Привет мир, hello again_2"),
+            ["2", "again", "hello", "world", "мир", "привет"],
+        );
+    }
+
+    #[test]
+    fn escape_string() {
+        let mut json = String::new();
+        string("a \"b\"\\\n\u{1}</", &mut json);
+        assert_eq!(json, r#""a \"b\"\\\n\u0001\u003c/""#);
+    }
+}
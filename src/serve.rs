@@ -0,0 +1,247 @@
+use {
+    crate::{cli::Build, date, log::info},
+    std::{
+        fmt::Write as _,
+        fs,
+        io::{BufRead, BufReader, Error, Write},
+        net::{TcpListener, TcpStream},
+        path::{Path, PathBuf},
+        sync::{
+            Arc,
+            atomic::{AtomicU64, Ordering},
+        },
+        thread,
+        time::{Duration, Instant, SystemTime},
+    },
+};
+
+/// The path polled by the live reload script to detect a rebuild.
+const VERSION_PATH: &str = "/.milky/version";
+
+pub fn serve(mut build: Build, port: u16) -> Result<(), Error> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .inspect_err(|_| eprintln!("failed to listen on the port {port}"))?;
+
+    // the local offset is unavailable once other threads are running,
+    // so the date is resolved only once
+    build.now.get_or_insert_with(date::now);
+    let build = &build;
+
+    let version = Arc::new(AtomicU64::new(0));
+    let mut sources = vec![];
+    rebuild(build, &mut sources);
+
+    thread::spawn({
+        let out = build.out.clone();
+        let version = Arc::clone(&version);
+        move || listen(listener, &out, &version)
+    });
+
+    info!("serving on http://127.0.0.1:{port}/");
+
+    let mut stamps = modified(&sources);
+    loop {
+        thread::sleep(Duration::from_millis(300));
+        if modified(&sources) == stamps {
+            continue;
+        }
+
+        info!("changes detected, rebuild");
+        rebuild(build, &mut sources);
+
+        stamps = modified(&sources);
+        version.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Rebuilds the blog and updates the watched sources.
+///
+/// A failed build keeps the previous sources and adds the ones it found,
+/// so fixing any of them triggers a rebuild.
+fn rebuild(build: &Build, sources: &mut Vec<PathBuf>) {
+    let start = Instant::now();
+    let mut found = vec![];
+    let res = crate::run(build, true, &mut found);
+    if res.is_ok() {
+        *sources = found;
+    } else {
+        for path in found {
+            if !sources.contains(&path) {
+                sources.push(path);
+            }
+        }
+    }
+
+    _ = crate::summary(start, res);
+}
+
+fn modified(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+        .collect()
+}
+
+fn listen(listener: TcpListener, out: &Path, version: &Arc<AtomicU64>) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+
+        let out = out.to_owned();
+        let version = Arc::clone(version);
+        thread::spawn(move || {
+            if let Err(e) = respond(stream, &out, &version) {
+                eprintln!("failed to respond: {e}");
+            }
+        });
+    }
+}
+
+fn respond(mut stream: TcpStream, out: &Path, version: &AtomicU64) -> Result<(), Error> {
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+
+    // skip headers
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return send(&mut stream, "400 Bad Request", "text/plain", b"bad request");
+    };
+
+    if method != "GET" {
+        return send(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            b"method not allowed",
+        );
+    }
+
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    if path == VERSION_PATH {
+        let version = version.load(Ordering::Relaxed).to_string();
+        return send(&mut stream, "200 OK", "text/plain", version.as_bytes());
+    }
+
+    let path = decode(path);
+    if path.split('/').any(|s| s == "..") {
+        return send(&mut stream, "403 Forbidden", "text/plain", b"forbidden");
+    }
+
+    let mut file = out.join(path.trim_start_matches('/'));
+    if file.is_dir() {
+        file.push("index.html");
+    }
+
+    match fs::read(&file) {
+        Ok(body) => send(&mut stream, "200 OK", content_type(&file), &body),
+        Err(_) if path == "/" => {
+            let body = index(out);
+            send(&mut stream, "200 OK", "text/html", body.as_bytes())
+        }
+        Err(_) => send(&mut stream, "404 Not Found", "text/plain", b"not found"),
+    }
+}
+
+fn send(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> Result<(), Error> {
+    let len = body.len();
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+        Content-Type: {content_type}\r\n\
+        Content-Length: {len}\r\n\
+        Cache-Control: no-store\r\n\
+        Connection: close\r\n\r\n",
+    )?;
+
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+/// Lists pages of the output root since the blog has no index page.
+fn index(out: &Path) -> String {
+    let mut pages: Vec<_> = fs::read_dir(out)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".html"))
+        .collect();
+
+    pages.sort();
+
+    let mut html = String::from("<!DOCTYPE html><meta charset=\"utf-8\"><ul>");
+    for page in pages {
+        _ = write!(html, "<li><a href=\"/{page}\">{page}</a></li>");
+    }
+
+    html.push_str("</ul>");
+    html
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("css") => "text/css",
+        Some("js") => "text/javascript",
+        Some("json") => "application/json",
+        Some("svg") => "image/svg+xml",
+        Some("woff2") => "font/woff2",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("png") => "image/png",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("atom") => "application/atom+xml",
+        Some("rss") => "application/rss+xml",
+        Some("xml") => "application/xml",
+        Some("txt") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Decodes a percent-encoded url path.
+fn decode(path: &str) -> String {
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let hex = tail
+            .get(..2)
+            .and_then(|hex| str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (b, hex) {
+            (b'%', Some(decoded)) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_path() {
+        assert_eq!(decode("/en/hello.html"), "/en/hello.html");
+        assert_eq!(decode("/a%20b%2"), "/a b%2");
+        assert_eq!(decode("/%D0%BF"), "/п");
+    }
+}
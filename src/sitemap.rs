@@ -0,0 +1,143 @@
+use {
+    crate::{
+        date::Date,
+        html::{self, Post},
+        lang::Lang,
+    },
+    std::{collections::BTreeMap, fmt::Write},
+};
+
+/// Creates the sitemap of list pages and listed articles.
+///
+/// Pages translated to other languages link them as alternates.
+pub fn sitemap(base_url: &str, posts: &BTreeMap<Lang, Vec<Post<'_>>>) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>");
+    xml.push_str(
+        "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+        xmlns:xhtml=\"http://www.w3.org/1999/xhtml\">",
+    );
+
+    let langs: Vec<_> = posts.keys().copied().collect();
+    for (&lang, lang_posts) in posts {
+        let lastmod = lang_posts.iter().map(Post::modified).max();
+        let alternates = langs.iter().map(|&lang| (lang, format!("{lang}.html")));
+        let href = format!("{lang}.html");
+        url(&mut xml, base_url, &href, lastmod, alternates);
+    }
+
+    let mut articles: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+    for (&lang, lang_posts) in posts {
+        for post in lang_posts {
+            articles.entry(post.name).or_default().insert(lang, post);
+        }
+    }
+
+    for (name, translations) in &articles {
+        for (&lang, post) in translations {
+            let alternates = translations
+                .keys()
+                .map(|&lang| (lang, format!("{lang}/{name}.html")));
+
+            let href = format!("{lang}/{name}.html");
+            url(&mut xml, base_url, &href, Some(post.modified()), alternates);
+        }
+    }
+
+    xml.push_str("</urlset>");
+    xml
+}
+
+fn url<A>(xml: &mut String, base_url: &str, href: &str, lastmod: Option<Date>, alternates: A)
+where
+    A: ExactSizeIterator<Item = (Lang, String)>,
+{
+    xml.push_str("<url><loc>");
+    html::escape(&format!("{base_url}/{href}"), xml);
+    xml.push_str("</loc>");
+    if let Some(lastmod) = lastmod {
+        _ = write!(xml, "<lastmod>{}</lastmod>", lastmod.datetime());
+    }
+
+    // a page without translations has no alternates
+    if alternates.len() > 1 {
+        for (lang, href) in alternates {
+            _ = write!(
+                xml,
+                "<xhtml:link rel=\"alternate\" hreflang=\"{lang}\" href=\""
+            );
+
+            html::escape(&format!("{base_url}/{href}"), xml);
+            xml.push_str("\"/>");
+        }
+    }
+
+    xml.push_str("</url>");
+}
+
+/// Creates the `robots.txt` with the given rules pointing at the sitemap.
+///
+/// Everything is allowed if there are no rules.
+pub fn robots(base_url: &str, rules: Option<&str>) -> String {
+    let rules = rules.unwrap_or("User-agent: *\nAllow: /");
+    format!("{}\n\nSitemap: {base_url}/sitemap.xml\n", rules.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternates() {
+        let post = |name| Post {
+            name,
+            title: "",
+            date: Date::parse("2024-02-03").expect("valid date"),
+            updated: None,
+            minutes: 1,
+            tags: &[],
+            description: None,
+            content: String::new(),
+            text: String::new(),
+        };
+
+        let lang = |s| Lang::from_ascii(s).expect("valid lang");
+        let posts = BTreeMap::from([
+            (lang(*b"en"), vec![post("hello"), post("only")]),
+            (lang(*b"ru"), vec![post("hello")]),
+        ]);
+
+        let xml = sitemap("https://example.com", &posts);
+        let alternate = |lang, href| {
+            format!(
+                "<xhtml:link rel=\"alternate\" hreflang=\"{lang}\" href=\"https://example.com/{href}\"/>"
+            )
+        };
+
+        assert!(
+            xml.contains(&format!(
+                "<url><loc>https://example.com/en/hello.html</loc>\
+                <lastmod>2024-02-03</lastmod>{}{}</url>",
+                alternate("en", "en/hello.html"),
+                alternate("ru", "ru/hello.html"),
+            )),
+            "translated article",
+        );
+
+        assert!(
+            xml.contains(
+                "<url><loc>https://example.com/en/only.html</loc>\
+                <lastmod>2024-02-03</lastmod></url>"
+            ),
+            "article without translations",
+        );
+    }
+
+    #[test]
+    fn robots_rules() {
+        assert_eq!(
+            robots("https://example.com", Some("User-agent: *\nDisallow: /\n")),
+            "User-agent: *\nDisallow: /\n\nSitemap: https://example.com/sitemap.xml\n",
+        );
+    }
+}